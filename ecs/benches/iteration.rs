@@ -0,0 +1,159 @@
+#![feature(test)]
+
+extern crate ecs;
+#[macro_use]
+extern crate ecs_derive;
+extern crate test;
+
+use ecs::component::storage::{ArchetypeStorage, PackedStorage, VecStorage};
+use ecs::component::{Component, ReadStorage, WriteStorage};
+use ecs::join::Join;
+use ecs::World;
+use test::Bencher;
+
+const ENTITIES: usize = 10_000;
+
+#[derive(Component, Clone, Copy)]
+#[Storage(VecStorage)]
+struct VecPosition([f32; 4]);
+
+#[derive(Component, Clone, Copy)]
+#[Storage(VecStorage)]
+struct VecVelocity([f32; 4]);
+
+#[derive(Component, Clone, Copy)]
+#[Storage(PackedStorage)]
+struct PackedPosition([f32; 4]);
+
+#[derive(Component, Clone, Copy)]
+#[Storage(PackedStorage)]
+struct PackedVelocity([f32; 4]);
+
+#[derive(Component, Clone, Copy)]
+#[Storage(ArchetypeStorage)]
+struct ArchetypePosition([f32; 4]);
+
+#[derive(Component, Clone, Copy)]
+#[Storage(ArchetypeStorage)]
+struct ArchetypeVelocity([f32; 4]);
+
+// Every third entity only has a position, the rest move.
+fn setup<P, V>(position: P, velocity: V) -> World
+where
+    P: Component + Copy,
+    V: Component + Copy,
+{
+    let mut world = World::new();
+    world.register::<P>();
+    world.register::<V>();
+
+    let entities = world.create_entities(ENTITIES);
+    world.exec(|(mut positions, mut velocities): (WriteStorage<P>, WriteStorage<V>)| {
+        for entity in entities {
+            positions.insert(entity, position);
+            if entity % 3 != 0 {
+                velocities.insert(entity, velocity);
+            }
+        }
+    });
+    world
+}
+
+// Reinserts half of the components so packed storages lose their order.
+fn churn<V>(world: &World, velocity: V)
+where
+    V: Component + Copy,
+{
+    world.exec(|mut velocities: WriteStorage<V>| {
+        for entity in (0..ENTITIES).filter(|entity| entity % 2 == 0) {
+            if velocities.remove(entity).is_some() {
+                velocities.insert(entity, velocity);
+            }
+        }
+    });
+}
+
+macro_rules! integrate {
+    ($world:expr, $position:ident, $velocity:ident) => {
+        $world.exec(
+            |(mut positions, velocities): (WriteStorage<$position>, ReadStorage<$velocity>)| {
+                for (position, velocity) in (&mut positions, &velocities).join() {
+                    for i in 0..4 {
+                        position.0[i] += velocity.0[i];
+                    }
+                }
+            },
+        )
+    };
+}
+
+macro_rules! sum {
+    ($world:expr, $position:ident, $velocity:ident) => {
+        $world.exec(
+            |(positions, velocities): (ReadStorage<$position>, ReadStorage<$velocity>)| {
+                let mut sum = [0.0; 4];
+                for (position, velocity) in (&positions, &velocities).join() {
+                    for i in 0..4 {
+                        sum[i] += position.0[i] * velocity.0[i];
+                    }
+                }
+                sum
+            },
+        )
+    };
+}
+
+#[bench]
+fn vec_join(b: &mut Bencher) {
+    let world = setup(VecPosition([0.0; 4]), VecVelocity([1.0; 4]));
+    b.iter(|| integrate!(world, VecPosition, VecVelocity));
+}
+
+#[bench]
+fn vec_read(b: &mut Bencher) {
+    let world = setup(VecPosition([0.0; 4]), VecVelocity([1.0; 4]));
+    b.iter(|| sum!(world, VecPosition, VecVelocity));
+}
+
+#[bench]
+fn packed_join(b: &mut Bencher) {
+    let world = setup(PackedPosition([0.0; 4]), PackedVelocity([1.0; 4]));
+    b.iter(|| integrate!(world, PackedPosition, PackedVelocity));
+}
+
+#[bench]
+fn packed_join_churned(b: &mut Bencher) {
+    let world = setup(PackedPosition([0.0; 4]), PackedVelocity([1.0; 4]));
+    churn(&world, PackedVelocity([1.0; 4]));
+    b.iter(|| integrate!(world, PackedPosition, PackedVelocity));
+}
+
+#[bench]
+fn packed_join_compacted(b: &mut Bencher) {
+    let world = setup(PackedPosition([0.0; 4]), PackedVelocity([1.0; 4]));
+    churn(&world, PackedVelocity([1.0; 4]));
+    world.compact();
+    b.iter(|| integrate!(world, PackedPosition, PackedVelocity));
+}
+
+#[bench]
+fn archetype_join(b: &mut Bencher) {
+    let world = setup(ArchetypePosition([0.0; 4]), ArchetypeVelocity([1.0; 4]));
+    world.maintain();
+    b.iter(|| integrate!(world, ArchetypePosition, ArchetypeVelocity));
+}
+
+#[bench]
+fn archetype_join_churned(b: &mut Bencher) {
+    let world = setup(ArchetypePosition([0.0; 4]), ArchetypeVelocity([1.0; 4]));
+    churn(&world, ArchetypeVelocity([1.0; 4]));
+    world.maintain();
+    b.iter(|| integrate!(world, ArchetypePosition, ArchetypeVelocity));
+}
+
+#[bench]
+fn archetype_read(b: &mut Bencher) {
+    let world = setup(ArchetypePosition([0.0; 4]), ArchetypeVelocity([1.0; 4]));
+    world.maintain();
+    b.iter(|| sum!(world, ArchetypePosition, ArchetypeVelocity));
+}
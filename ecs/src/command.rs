@@ -0,0 +1,78 @@
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+use super::component::storage::MaskedStorage;
+use super::component::Component;
+use super::entity::{Entity, EntityStorage, LOCK_POISOINED};
+use super::World;
+
+pub trait Command: Send {
+    fn apply(self: Box<Self>, world: &World);
+}
+
+impl<F> Command for F
+where
+    F: FnOnce(&World) + Send,
+{
+    fn apply(self: Box<Self>, world: &World) {
+        (*self)(world)
+    }
+}
+
+// Work queued from places that can't touch the world directly, like
+// component hooks. Everything is applied in order by `World::maintain`.
+#[derive(Clone, Default)]
+pub struct Commands {
+    queue: Arc<Mutex<Vec<Box<Command>>>>,
+}
+
+impl Commands {
+    pub fn exec<F>(&self, f: F)
+    where
+        F: 'static + FnOnce(&World) + Send,
+    {
+        self.queue.lock().expect(LOCK_POISOINED).push(Box::new(f));
+    }
+
+    // Skipped if the entity is dead by the time the command is applied.
+    pub fn insert<T>(&self, entity: Entity, component: T)
+    where
+        T: Component + Send,
+    {
+        self.exec(move |world| {
+            if world.fetch::<EntityStorage>().is_alive(entity) {
+                world
+                    .fetch_mut::<MaskedStorage<T>>()
+                    .insert(entity, component)
+            }
+        });
+    }
+
+    // Skipped if the entity is dead by the time the command is applied.
+    pub fn remove<T>(&self, entity: Entity)
+    where
+        T: Component,
+    {
+        self.exec(move |world| {
+            if world.fetch::<EntityStorage>().is_alive(entity) {
+                world.fetch_mut::<MaskedStorage<T>>().discard(entity);
+            }
+        });
+    }
+
+    pub fn destroy(&self, entity: Entity) {
+        self.exec(move |world| {
+            if world.fetch::<EntityStorage>().is_alive(entity) {
+                world.destroy(entity);
+            }
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.lock().expect(LOCK_POISOINED).is_empty()
+    }
+
+    pub(crate) fn take(&self) -> Vec<Box<Command>> {
+        mem::take(&mut *self.queue.lock().expect(LOCK_POISOINED))
+    }
+}
@@ -0,0 +1,131 @@
+use super::super::entity::Entity;
+use super::super::resource::Resources;
+use super::storage::MaskedStorage;
+use super::Component;
+
+// A group of components inserted into and removed from an entity together.
+pub trait Bundle: Sized {
+    fn insert(self, resources: &Resources, entity: Entity);
+
+    // Removes every component of the bundle, returning them only if the
+    // entity had all of them.
+    fn remove(resources: &Resources, entity: Entity) -> Option<Self>;
+}
+
+macro_rules! impl_bundle {
+    ( $($ty:ident),* ) => {
+        impl<$($ty),*> Bundle for ( $( $ty , )* )
+            where $( $ty : Component ),*
+        {
+            fn insert(self, resources: &Resources, entity: Entity) {
+                #![allow(non_snake_case)]
+
+                let ( $($ty,)* ) = self;
+                $( resources.fetch_mut::<MaskedStorage<$ty>>().insert(entity, $ty); )*
+            }
+
+            fn remove(resources: &Resources, entity: Entity) -> Option<Self> {
+                #![allow(non_snake_case)]
+
+                $( let $ty = resources.fetch_mut::<MaskedStorage<$ty>>().remove(entity); )*
+                Some(( $( $ty?, )* ))
+            }
+        }
+    };
+}
+
+mod impl_bundle {
+    #![cfg_attr(rustfmt, rustfmt_skip)]
+
+    use super::*;
+
+    impl_bundle!(A);
+    impl_bundle!(A, B);
+    impl_bundle!(A, B, C);
+    impl_bundle!(A, B, C, D);
+    impl_bundle!(A, B, C, D, E);
+    impl_bundle!(A, B, C, D, E, F);
+    impl_bundle!(A, B, C, D, E, F, G);
+    impl_bundle!(A, B, C, D, E, F, G, H);
+    impl_bundle!(A, B, C, D, E, F, G, H, I);
+    impl_bundle!(A, B, C, D, E, F, G, H, I, J);
+    impl_bundle!(A, B, C, D, E, F, G, H, I, J, K);
+    impl_bundle!(A, B, C, D, E, F, G, H, I, J, K, L);
+    impl_bundle!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+    impl_bundle!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+    impl_bundle!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+    impl_bundle!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+    impl_bundle!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q);
+    impl_bundle!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R);
+    impl_bundle!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S);
+    impl_bundle!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T);
+    impl_bundle!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U);
+    impl_bundle!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V);
+    impl_bundle!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W);
+    impl_bundle!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X);
+    impl_bundle!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y);
+    impl_bundle!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::storage::VecStorage;
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Position(i32);
+    impl Component for Position {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Velocity(i32);
+    impl Component for Velocity {
+        type Storage = VecStorage<Self>;
+    }
+
+    fn resources() -> Resources {
+        let mut resources = Resources::new();
+        resources.add(<MaskedStorage<Position>>::new());
+        resources.add(<MaskedStorage<Velocity>>::new());
+        resources
+    }
+
+    #[test]
+    fn insert() {
+        let resources = resources();
+        (Position(1), Velocity(2)).insert(&resources, 0);
+
+        assert_eq!(
+            resources.fetch::<MaskedStorage<Position>>().get(0),
+            Some(&Position(1))
+        );
+        assert_eq!(
+            resources.fetch::<MaskedStorage<Velocity>>().get(0),
+            Some(&Velocity(2))
+        );
+    }
+
+    #[test]
+    fn remove() {
+        let resources = resources();
+        (Position(1), Velocity(2)).insert(&resources, 0);
+
+        assert_eq!(
+            <(Position, Velocity)>::remove(&resources, 0),
+            Some((Position(1), Velocity(2)))
+        );
+        assert!(!resources.fetch::<MaskedStorage<Position>>().contains(0));
+        assert!(!resources.fetch::<MaskedStorage<Velocity>>().contains(0));
+    }
+
+    #[test]
+    fn remove_partial() {
+        let resources = resources();
+        (Position(1),).insert(&resources, 0);
+
+        assert_eq!(<(Position, Velocity)>::remove(&resources, 0), None);
+        // still removed
+        assert!(!resources.fetch::<MaskedStorage<Position>>().contains(0));
+    }
+}
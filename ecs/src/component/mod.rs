@@ -1,28 +1,32 @@
+pub mod bundle;
+pub mod registry;
 pub mod storage;
 
 use std::any::Any;
 use std::marker::PhantomData;
+use std::sync::Arc;
 use std::ops::{Deref, DerefMut};
 
-use self::storage::{MaskedStorage, RawStorage};
+use bit_set::BitSet;
+
+use self::storage::{
+    ArchetypeId, Chunks, ComponentEvent, Drain, MaskedStorage, RawStorage, SharedStorage,
+};
+use super::command::Commands;
 use super::entity::{Entity, EntityStorage};
+use super::event::ReaderId;
+use super::join::{ChunkedJoin, Join};
 use super::resource::{Fetch, FetchMut, Resources};
 use super::system::SystemData;
 
 pub trait Component: Any + Sized {
     type Storage: RawStorage<Self> + Any + Send + Sync;
-}
 
-trait EntityChecker {
-    fn assert_alive(&self, entity: Entity);
-}
+    // Hooks for components owning something outside the world. Removal
+    // covers replacement, clearing and destroying the entity.
+    fn on_insert(&mut self, _entity: Entity, _commands: &Commands) {}
 
-impl EntityChecker for EntityStorage {
-    fn assert_alive(&self, entity: Entity) {
-        if !self.is_alive(entity) {
-            panic!("Entity {} is not alive.", entity);
-        }
-    }
+    fn on_remove(&mut self, _entity: Entity, _commands: &Commands) {}
 }
 
 pub struct Storage<'a, T, D>
@@ -50,6 +54,18 @@ where
         self.entities.assert_alive(entity);
         self.data.get(entity)
     }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn events(&self, reader: &mut ReaderId) -> &[ComponentEvent] {
+        self.data.events(reader)
+    }
 }
 
 impl<'a, T> SystemData<'a> for ReadStorage<'a, T>
@@ -79,6 +95,10 @@ where
         self.data.get_mut(entity)
     }
 
+    pub fn track(&mut self) -> ReaderId {
+        self.data.track()
+    }
+
     pub fn insert(&mut self, entity: Entity, component: T) {
         self.entities.assert_alive(entity);
         self.data.insert(entity, component)
@@ -88,6 +108,65 @@ where
         self.entities.assert_alive(entity);
         self.data.remove(entity)
     }
+
+    pub fn clear(&mut self) {
+        self.data.clear()
+    }
+
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(Entity, &T) -> bool,
+    {
+        self.data.retain(f)
+    }
+
+    pub fn retain_mut<F>(&mut self, f: F)
+    where
+        F: FnMut(Entity, &mut T) -> bool,
+    {
+        self.data.retain_mut(f)
+    }
+
+    pub fn drain(&mut self) -> Drain<T> {
+        self.data.drain()
+    }
+
+    pub fn extend<I>(&mut self, components: I)
+    where
+        I: IntoIterator<Item = (Entity, T)>,
+    {
+        let entities = &self.entities;
+        self.data
+            .extend(components.into_iter().inspect(|&(entity, _)| {
+                entities.assert_alive(entity);
+            }))
+    }
+}
+
+impl<'a, T, D> Storage<'a, T, D>
+where
+    T: Component<Storage = SharedStorage<T>> + Clone,
+    D: Deref<Target = MaskedStorage<T>>,
+{
+    pub fn shared(&self, entity: Entity) -> Option<&Arc<T>> {
+        self.entities.assert_alive(entity);
+        self.data.shared(entity)
+    }
+
+    pub fn group_by<J: Join>(&self, join: J) -> Vec<(&T, Vec<J::Item>)> {
+        self.data.group_by(join)
+    }
+}
+
+impl<'a, T, D> Storage<'a, T, D>
+where
+    T: Component<Storage = SharedStorage<T>> + Clone,
+    D: DerefMut<Target = MaskedStorage<T>>,
+{
+    pub fn insert_shared(&mut self, entity: Entity, value: Arc<T>) {
+        self.entities.assert_alive(entity);
+        self.data.insert_shared(entity, value)
+    }
 }
 
 impl<'a, T> SystemData<'a> for WriteStorage<'a, T>
@@ -105,6 +184,83 @@ where
     }
 }
 
+impl<'a, 'b, T, D> Join for &'b Storage<'a, T, D>
+where
+    T: Component,
+    D: Deref<Target = MaskedStorage<T>>,
+{
+    type Item = &'b T;
+
+    fn open(&self) -> BitSet {
+        self.data.entities()
+    }
+
+    unsafe fn get(&mut self, index: usize) -> &'b T {
+        let storage: &'b Storage<'a, T, D> = *self;
+        storage.data.get(index).expect("Component not found!")
+    }
+
+    fn chunked(&mut self) -> Option<&mut ChunkedJoin<Item = &'b T>> {
+        Some(self)
+    }
+}
+
+impl<'a, 'b, T, D> ChunkedJoin for &'b Storage<'a, T, D>
+where
+    T: Component,
+    D: Deref<Target = MaskedStorage<T>>,
+{
+    type Item = &'b T;
+
+    fn chunks(&mut self) -> Option<Chunks> {
+        self.data.chunks()
+    }
+
+    unsafe fn get_row(&mut self, archetype: ArchetypeId, row: usize) -> &'b T {
+        let storage: &'b Storage<'a, T, D> = *self;
+        storage.data.row(archetype, row)
+    }
+}
+
+impl<'a, 'b, T, D> Join for &'b mut Storage<'a, T, D>
+where
+    T: Component,
+    D: DerefMut<Target = MaskedStorage<T>>,
+{
+    type Item = &'b mut T;
+
+    fn open(&self) -> BitSet {
+        self.data.entities()
+    }
+
+    unsafe fn get(&mut self, index: usize) -> &'b mut T {
+        let data: *mut MaskedStorage<T> = &mut *self.data;
+        // see the MaskedStorage impl, callers never repeat an index
+        (*data).get_mut(index).expect("Component not found!")
+    }
+
+    fn chunked(&mut self) -> Option<&mut ChunkedJoin<Item = &'b mut T>> {
+        Some(self)
+    }
+}
+
+impl<'a, 'b, T, D> ChunkedJoin for &'b mut Storage<'a, T, D>
+where
+    T: Component,
+    D: DerefMut<Target = MaskedStorage<T>>,
+{
+    type Item = &'b mut T;
+
+    fn chunks(&mut self) -> Option<Chunks> {
+        self.data.chunks()
+    }
+
+    unsafe fn get_row(&mut self, archetype: ArchetypeId, row: usize) -> &'b mut T {
+        let data: *mut MaskedStorage<T> = &mut *self.data;
+        (*data).row_mut(archetype, row)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use self::storage::VecStorage;
@@ -157,6 +313,16 @@ mod tests {
         storage.insert(0, MyComponent);
     }
 
+    #[test]
+    #[should_panic]
+    fn extend_dead_entity() {
+        let mut resources = Resources::new();
+        resources.add(EntityStorage::new());
+        resources.add(<MaskedStorage<MyComponent>>::new());
+        let mut storage = MyWriteStorage::fetch(&resources);
+        storage.extend(vec![(0, MyComponent)]);
+    }
+
     #[test]
     #[should_panic]
     fn remove_dead_entity() {
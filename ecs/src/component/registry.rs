@@ -0,0 +1,270 @@
+use std::any::{type_name, Any, TypeId};
+use std::fmt;
+
+use super::super::entity::{Entity, EntityStorage};
+use super::super::resource::Resources;
+use super::super::snapshot::{self, RestoreFn, Snapshot, SnapshotFn};
+use super::storage::MaskedStorage;
+use super::Component;
+
+type DebugFn = fn(&Resources, Entity, &mut fmt::Formatter) -> fmt::Result;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ComponentInfo {
+    pub id: TypeId,
+    pub name: &'static str,
+    pub count: usize,
+}
+
+// Type-erased handle to a registered MaskedStorage<T>.
+struct Entry {
+    id: TypeId,
+    name: &'static str,
+    count: fn(&Resources) -> usize,
+    contains: fn(&Resources, Entity) -> bool,
+    remove: fn(&Resources, Entity),
+    forget: fn(&Resources, Entity),
+    migrate: fn(&Resources, Entity, &Resources, Entity),
+    compact: fn(&Resources),
+    maintain: fn(&Resources),
+    // indices and orders following the storage, synced after `maintain`
+    views: Vec<fn(&Resources)>,
+    debug: Option<DebugFn>,
+    snapshot: Option<(SnapshotFn, RestoreFn)>,
+}
+
+impl Entry {
+    fn info(&self, resources: &Resources) -> ComponentInfo {
+        ComponentInfo {
+            id: self.id,
+            name: self.name,
+            count: (self.count)(resources),
+        }
+    }
+}
+
+fn count<T: Component>(resources: &Resources) -> usize {
+    resources.fetch::<MaskedStorage<T>>().len()
+}
+
+fn contains<T: Component>(resources: &Resources, entity: Entity) -> bool {
+    resources.fetch::<MaskedStorage<T>>().contains(entity)
+}
+
+fn remove<T: Component>(resources: &Resources, entity: Entity) {
+    resources.fetch_mut::<MaskedStorage<T>>().discard(entity);
+}
+
+fn forget<T: Component>(resources: &Resources, entity: Entity) {
+    resources.fetch_mut::<MaskedStorage<T>>().forget(entity);
+}
+
+fn migrate<T: Component>(source: &Resources, entity: Entity, target: &Resources, to: Entity) {
+    // a move, not a removal and an insertion, so hooks stay out of it
+    if let Some(component) = source.fetch_mut::<MaskedStorage<T>>().take(entity) {
+        target.fetch_mut::<MaskedStorage<T>>().put(to, component);
+    }
+}
+
+fn compact<T: Component>(resources: &Resources) {
+    resources.fetch_mut::<MaskedStorage<T>>().compact();
+}
+
+fn maintain<T: Component>(resources: &Resources) {
+    resources.fetch_mut::<MaskedStorage<T>>().maintain();
+}
+
+fn debug<T: Component + fmt::Debug>(
+    resources: &Resources,
+    entity: Entity,
+    f: &mut fmt::Formatter,
+) -> fmt::Result {
+    match resources.fetch::<MaskedStorage<T>>().get(entity) {
+        Some(component) => fmt::Debug::fmt(component, f),
+        None => f.write_str("<missing>"),
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct ComponentRegistry {
+    entries: Vec<Entry>,
+}
+
+impl ComponentRegistry {
+    pub fn register<T: Component>(&mut self) {
+        self.entries.push(Entry {
+            id: TypeId::of::<T>(),
+            name: type_name::<T>(),
+            count: count::<T>,
+            contains: contains::<T>,
+            remove: remove::<T>,
+            forget: forget::<T>,
+            migrate: migrate::<T>,
+            compact: compact::<T>,
+            maintain: maintain::<T>,
+            views: Vec::new(),
+            debug: None,
+            snapshot: None,
+        });
+    }
+
+    pub fn enable_debug<T: Component + fmt::Debug>(&mut self) {
+        self.entry_mut::<T>().debug = Some(debug::<T>);
+    }
+
+    pub fn add_view<T: Component>(&mut self, view: fn(&Resources)) {
+        self.entry_mut::<T>().views.push(view);
+    }
+
+    pub fn enable_snapshot<T: Component + Clone>(&mut self) {
+        self.entry_mut::<T>().snapshot = Some((
+            snapshot::snapshot_component::<T>,
+            snapshot::restore_component::<T>,
+        ));
+    }
+
+    pub fn snapshot(&self, resources: &Resources) -> Vec<(TypeId, Box<Any>)> {
+        self.entries
+            .iter()
+            .filter_map(|entry| {
+                entry
+                    .snapshot
+                    .map(|(snapshot, _)| (entry.id, snapshot(resources)))
+            })
+            .collect()
+    }
+
+    pub fn restore(&self, resources: &Resources, snapshot: &Snapshot) {
+        for entry in self.entries.iter() {
+            if let Some((_, restore)) = entry.snapshot {
+                if let Some(data) = snapshot.component(entry.id) {
+                    restore(resources, data);
+                }
+            }
+        }
+    }
+
+    pub fn components(&self, resources: &Resources) -> Vec<ComponentInfo> {
+        self.entries
+            .iter()
+            .map(|entry| entry.info(resources))
+            .collect()
+    }
+
+    pub fn composition(&self, resources: &Resources, entity: Entity) -> Vec<ComponentInfo> {
+        resources.fetch::<EntityStorage>().assert_alive(entity);
+        self.entries
+            .iter()
+            .filter(|entry| (entry.contains)(resources, entity))
+            .map(|entry| entry.info(resources))
+            .collect()
+    }
+
+    pub fn remove_all(&self, resources: &Resources, entity: Entity) {
+        for entry in self.entries.iter() {
+            (entry.remove)(resources, entity);
+        }
+    }
+
+    // Same as `remove_all` without running hooks.
+    pub fn forget_all(&self, resources: &Resources, entity: Entity) {
+        for entry in self.entries.iter() {
+            (entry.forget)(resources, entity);
+        }
+    }
+
+    pub fn contains<T: Component>(&self) -> bool {
+        let id = TypeId::of::<T>();
+        self.entries.iter().any(|entry| entry.id == id)
+    }
+
+    // Panics if the entity has a component the target doesn't know about.
+    pub fn assert_migratable(&self, resources: &Resources, entity: Entity, target: &ComponentRegistry) {
+        for entry in self.entries.iter() {
+            if (entry.contains)(resources, entity)
+                && !target.entries.iter().any(|other| other.id == entry.id)
+            {
+                panic!("Component {} is not registered in the target world!", entry.name);
+            }
+        }
+    }
+
+    pub fn migrate(&self, resources: &Resources, entity: Entity, target: &Resources, to: Entity) {
+        for entry in self.entries.iter() {
+            (entry.migrate)(resources, entity, target, to);
+        }
+    }
+
+    pub fn compact(&self, resources: &Resources) {
+        for entry in self.entries.iter() {
+            (entry.compact)(resources);
+        }
+    }
+
+    pub fn maintain(&self, resources: &Resources) {
+        for entry in self.entries.iter() {
+            (entry.maintain)(resources);
+            for view in entry.views.iter() {
+                view(resources);
+            }
+        }
+    }
+
+    pub fn dump<'a>(&'a self, resources: &'a Resources, entity: Entity) -> EntityDump<'a> {
+        resources.fetch::<EntityStorage>().assert_alive(entity);
+        EntityDump {
+            registry: self,
+            resources,
+            entity,
+        }
+    }
+
+    fn entry_mut<T: Component>(&mut self) -> &mut Entry {
+        let id = TypeId::of::<T>();
+        self.entries
+            .iter_mut()
+            .find(|entry| entry.id == id)
+            .expect("Component not registered!")
+    }
+}
+
+pub struct EntityDump<'a> {
+    registry: &'a ComponentRegistry,
+    resources: &'a Resources,
+    entity: Entity,
+}
+
+struct Field<'a> {
+    resources: &'a Resources,
+    entity: Entity,
+    debug: Option<DebugFn>,
+}
+
+impl<'a> fmt::Debug for Field<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.debug {
+            Some(debug) => debug(self.resources, self.entity, f),
+            None => f.write_str(".."),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for EntityDump<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = format!("Entity({})", self.entity);
+        let mut dump = f.debug_struct(&name);
+        for entry in self.registry.entries.iter() {
+            if (entry.contains)(self.resources, self.entity) {
+                dump.field(
+                    entry.name,
+                    &Field {
+                        resources: self.resources,
+                        entity: self.entity,
+                        debug: entry.debug,
+                    },
+                );
+            }
+        }
+        dump.finish()
+    }
+}
@@ -0,0 +1,508 @@
+use fxhash::FxHashMap;
+use std::any::TypeId;
+use std::mem;
+use std::sync::{Arc, Mutex};
+use std::vec::IntoIter;
+
+use super::super::super::super::entity::LOCK_POISOINED;
+use super::*;
+
+pub type ArchetypeId = usize;
+
+// The empty set, where entities without archetype components live.
+const EMPTY: ArchetypeId = 0;
+
+struct ArchetypeTable {
+    components: FxHashMap<TypeId, usize>,
+    // sorted component ids of each archetype
+    archetypes: Vec<Vec<usize>>,
+    lookup: FxHashMap<Vec<usize>, ArchetypeId>,
+    entities: Vec<ArchetypeId>,
+    // per component, entities that changed archetype while holding it
+    moved: Vec<BitSet>,
+}
+
+impl Default for ArchetypeTable {
+    fn default() -> Self {
+        let mut lookup = FxHashMap::default();
+        lookup.insert(Vec::new(), EMPTY);
+        ArchetypeTable {
+            components: Default::default(),
+            archetypes: vec![Vec::new()],
+            lookup,
+            entities: Vec::new(),
+            moved: Vec::new(),
+        }
+    }
+}
+
+impl ArchetypeTable {
+    fn archetype(&self, entity: Index) -> ArchetypeId {
+        self.entities.get(entity).cloned().unwrap_or(EMPTY)
+    }
+
+    fn find(&mut self, components: Vec<usize>) -> ArchetypeId {
+        if let Some(archetype) = self.lookup.get(&components) {
+            return *archetype;
+        }
+
+        let archetype = self.archetypes.len();
+        self.archetypes.push(components.clone());
+        self.lookup.insert(components, archetype);
+        archetype
+    }
+
+    fn relocate(&mut self, entity: Index, components: Vec<usize>) -> ArchetypeId {
+        // components the entity keeps are left in the chunk of the old archetype
+        for component in self.archetypes[self.archetype(entity)].iter() {
+            if components.binary_search(component).is_ok() {
+                self.moved[*component].insert(entity);
+            }
+        }
+
+        let archetype = self.find(components);
+        let len = self.entities.len();
+        if len <= entity {
+            self.entities.extend((len..entity + 1).map(|_| EMPTY));
+        }
+        self.entities[entity] = archetype;
+        archetype
+    }
+}
+
+// Tracks which archetype components each entity holds, shared by all the
+// `ArchetypeStorage`s of a world. Only components stored in an
+// `ArchetypeStorage` count towards an entity's archetype.
+#[derive(Clone, Default)]
+pub struct Archetypes {
+    table: Arc<Mutex<ArchetypeTable>>,
+}
+
+impl Archetypes {
+    pub fn archetype(&self, entity: Index) -> ArchetypeId {
+        self.table.lock().expect(LOCK_POISOINED).archetype(entity)
+    }
+
+    fn component<T: Component>(&self) -> usize {
+        let mut table = self.table.lock().expect(LOCK_POISOINED);
+        let next = table.components.len();
+        let component = *table.components.entry(TypeId::of::<T>()).or_insert(next);
+        if component == next {
+            table.moved.push(BitSet::new());
+        }
+        component
+    }
+
+    // Returns the archetype the entity ends up in.
+    fn add(&self, entity: Index, component: usize) -> ArchetypeId {
+        let mut table = self.table.lock().expect(LOCK_POISOINED);
+        let mut components = table.archetypes[table.archetype(entity)].clone();
+        if let Err(position) = components.binary_search(&component) {
+            components.insert(position, component);
+        }
+        table.relocate(entity, components)
+    }
+
+    fn remove(&self, entity: Index, component: usize) {
+        let mut table = self.table.lock().expect(LOCK_POISOINED);
+        let mut components = table.archetypes[table.archetype(entity)].clone();
+        components.retain(|other| *other != component);
+        table.relocate(entity, components);
+    }
+
+    fn take_moved(&self, component: usize) -> BitSet {
+        let mut table = self.table.lock().expect(LOCK_POISOINED);
+        mem::replace(&mut table.moved[component], BitSet::new())
+    }
+
+    fn has_moved(&self, component: usize) -> bool {
+        !self.table.lock().expect(LOCK_POISOINED).moved[component].is_empty()
+    }
+}
+
+// Archetype and length of the chunks a join walks instead of its mask.
+pub struct Chunks {
+    archetypes: Archetypes,
+    chunks: Vec<(ArchetypeId, usize)>,
+}
+
+impl Chunks {
+    // Chunks of the archetypes both sides have, `None` if they belong to
+    // different worlds or disagree on a length.
+    pub fn intersect(self, other: &Chunks) -> Option<Chunks> {
+        if !Arc::ptr_eq(&self.archetypes.table, &other.archetypes.table) {
+            return None;
+        }
+
+        let mut chunks = Vec::with_capacity(self.chunks.len());
+        for (archetype, len) in self.chunks {
+            match other.chunks.iter().find(|chunk| chunk.0 == archetype) {
+                Some(chunk) if chunk.1 != len => return None,
+                Some(_) => chunks.push((archetype, len)),
+                None => {}
+            }
+        }
+
+        Some(Chunks {
+            archetypes: self.archetypes,
+            chunks,
+        })
+    }
+}
+
+impl IntoIterator for Chunks {
+    type Item = (ArchetypeId, usize);
+    type IntoIter = IntoIter<(ArchetypeId, usize)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.chunks.into_iter()
+    }
+}
+
+struct Chunk<T> {
+    archetype: ArchetypeId,
+    indices: Vec<Index>,
+    data: Vec<T>,
+    sorted: bool,
+}
+
+// Components of entities with the same archetype sit back to back in one
+// chunk, in index order. Joins over archetype components zip their chunks
+// instead of looking every index up, visiting entities chunk by chunk.
+// Entities that changed archetype are moved to their new chunk by
+// `World::maintain`, until then joins fall back to the mask.
+pub struct ArchetypeStorage<T: Component> {
+    archetypes: Archetypes,
+    component: usize,
+    chunks: Vec<Chunk<T>>,
+    // position in `chunks` of each archetype's chunk
+    positions: Vec<Option<usize>>,
+    locations: Vec<Option<(usize, usize)>>,
+}
+
+impl<T> Default for ArchetypeStorage<T>
+where
+    T: Component,
+{
+    // Archetypes of its own, until `attach` hands it the world's.
+    fn default() -> Self {
+        let archetypes = Archetypes::default();
+        let component = archetypes.component::<T>();
+        ArchetypeStorage {
+            archetypes,
+            component,
+            chunks: Vec::new(),
+            positions: Vec::new(),
+            locations: Vec::new(),
+        }
+    }
+}
+
+impl<T> ArchetypeStorage<T>
+where
+    T: Component,
+{
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn location(&self, index: Index) -> (usize, usize) {
+        self.locations
+            .get(index)
+            .and_then(|location| *location)
+            .expect(MISSING_COMPONENT)
+    }
+
+    fn chunk(&mut self, archetype: ArchetypeId) -> usize {
+        let len = self.positions.len();
+        if len <= archetype {
+            self.positions.extend((len..archetype + 1).map(|_| None));
+        }
+
+        if let Some(position) = self.positions[archetype] {
+            return position;
+        }
+
+        self.chunks.push(Chunk {
+            archetype,
+            indices: Vec::new(),
+            data: Vec::new(),
+            sorted: true,
+        });
+        self.positions[archetype] = Some(self.chunks.len() - 1);
+        self.chunks.len() - 1
+    }
+
+    fn position(&self, archetype: ArchetypeId) -> usize {
+        self.positions[archetype].expect("Archetype not found!")
+    }
+
+    fn push(&mut self, index: Index, archetype: ArchetypeId, component: T) {
+        let chunk = self.chunk(archetype);
+        let row = {
+            let chunk = &mut self.chunks[chunk];
+            if chunk.indices.last().map_or(false, |last| *last > index) {
+                chunk.sorted = false;
+            }
+            chunk.indices.push(index);
+            chunk.data.push(component);
+            chunk.data.len() - 1
+        };
+
+        let len = self.locations.len();
+        if len <= index {
+            self.locations.extend((len..index + 1).map(|_| None));
+        }
+        self.locations[index] = Some((chunk, row));
+    }
+
+    fn take(&mut self, index: Index) -> T {
+        let (position, row) = self.location(index);
+        self.locations[index] = None;
+
+        let chunk = &mut self.chunks[position];
+        chunk.indices.swap_remove(row);
+        if let Some(moved) = chunk.indices.get(row) {
+            self.locations[*moved] = Some((position, row));
+            chunk.sorted = false;
+        }
+        chunk.data.swap_remove(row)
+    }
+
+    fn sort(&mut self) {
+        for (position, chunk) in self.chunks.iter_mut().enumerate() {
+            if chunk.sorted {
+                continue;
+            }
+
+            let mut components: Vec<(Index, T)> =
+                chunk.indices.drain(..).zip(chunk.data.drain(..)).collect();
+            components.sort_unstable_by_key(|&(index, _)| index);
+
+            for (row, (index, component)) in components.into_iter().enumerate() {
+                self.locations[index] = Some((position, row));
+                chunk.indices.push(index);
+                chunk.data.push(component);
+            }
+            chunk.sorted = true;
+        }
+    }
+}
+
+impl<T> RawStorage<T> for ArchetypeStorage<T>
+where
+    T: Component,
+{
+    fn get(&self, index: Index) -> &T {
+        let (chunk, row) = self.location(index);
+        &self.chunks[chunk].data[row]
+    }
+
+    fn contains(&self, index: Index) -> bool {
+        match self.locations.get(index) {
+            Some(&Some(_)) => true,
+            _ => false,
+        }
+    }
+
+    fn get_mut(&mut self, index: Index) -> &mut T {
+        let (chunk, row) = self.location(index);
+        &mut self.chunks[chunk].data[row]
+    }
+
+    fn insert(&mut self, index: Index, component: T) {
+        if self.contains(index) {
+            *self.get_mut(index) = component;
+            return;
+        }
+
+        let archetype = self.archetypes.add(index, self.component);
+        self.push(index, archetype, component);
+    }
+
+    fn remove(&mut self, index: Index) -> T {
+        let component = self.take(index);
+        self.archetypes.remove(index, self.component);
+        component
+    }
+
+    fn chunked(&self) -> Option<&ChunkedStorage<T>> {
+        Some(self)
+    }
+
+    fn chunked_mut(&mut self) -> Option<&mut ChunkedStorage<T>> {
+        Some(self)
+    }
+
+    fn attach(&mut self, archetypes: &Archetypes) {
+        assert!(
+            self.locations.is_empty(),
+            "Can't attach a storage that holds components!"
+        );
+        self.component = archetypes.component::<T>();
+        self.archetypes = archetypes.clone();
+    }
+
+    fn maintain(&mut self) {
+        for index in self.archetypes.take_moved(self.component).iter() {
+            if !self.contains(index) {
+                continue;
+            }
+
+            let archetype = self.archetypes.archetype(index);
+            let (chunk, _) = self.location(index);
+            if self.chunks[chunk].archetype != archetype {
+                let component = self.take(index);
+                self.push(index, archetype, component);
+            }
+        }
+        self.sort();
+    }
+
+    fn compact(&mut self) {
+        self.maintain();
+    }
+}
+
+impl<T> ChunkedStorage<T> for ArchetypeStorage<T>
+where
+    T: Component,
+{
+    fn chunks(&self) -> Option<Chunks> {
+        if self.chunks.iter().any(|chunk| !chunk.sorted)
+            || self.archetypes.has_moved(self.component)
+        {
+            return None;
+        }
+
+        Some(Chunks {
+            archetypes: self.archetypes.clone(),
+            chunks: self.chunks
+                .iter()
+                .map(|chunk| (chunk.archetype, chunk.data.len()))
+                .collect(),
+        })
+    }
+
+    fn row(&self, archetype: ArchetypeId, row: usize) -> &T {
+        &self.chunks[self.position(archetype)].data[row]
+    }
+
+    fn row_mut(&mut self, archetype: ArchetypeId, row: usize) -> (Index, &mut T) {
+        let position = self.position(archetype);
+        let chunk = &mut self.chunks[position];
+        (chunk.indices[row], &mut chunk.data[row])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Position(i32);
+    impl Component for Position {
+        type Storage = ArchetypeStorage<Self>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Velocity(i32);
+    impl Component for Velocity {
+        type Storage = ArchetypeStorage<Self>;
+    }
+
+    fn layout<T: Component>(storage: &ArchetypeStorage<T>) -> Vec<(ArchetypeId, &[Index], &[T])> {
+        storage
+            .chunks
+            .iter()
+            .filter(|chunk| !chunk.data.is_empty())
+            .map(|chunk| (chunk.archetype, &chunk.indices[..], &chunk.data[..]))
+            .collect()
+    }
+
+    fn setup() -> (Archetypes, ArchetypeStorage<Position>, ArchetypeStorage<Velocity>) {
+        let archetypes = Archetypes::default();
+        let mut positions = ArchetypeStorage::new();
+        let mut velocities = ArchetypeStorage::new();
+        positions.attach(&archetypes);
+        velocities.attach(&archetypes);
+        (archetypes, positions, velocities)
+    }
+
+    #[test]
+    fn archetype_storage_insert() {
+        let mut storage: ArchetypeStorage<Position> = ArchetypeStorage::new();
+        storage.insert(5, Position(5));
+        storage.insert(2, Position(2));
+        storage.insert(2, Position(3));
+        assert!(!storage.contains(0));
+        assert_eq!(storage.get(2).0, 3);
+        assert_eq!(storage.get(5).0, 5);
+    }
+
+    #[test]
+    fn archetype_storage_remove() {
+        let mut storage: ArchetypeStorage<Position> = ArchetypeStorage::new();
+        storage.insert(0, Position(0));
+        storage.insert(1, Position(1));
+        storage.insert(2, Position(2));
+        assert_eq!(storage.remove(0).0, 0);
+        assert!(!storage.contains(0));
+        assert_eq!(storage.get(1).0, 1);
+        assert_eq!(storage.get(2).0, 2);
+        assert_eq!(storage.remove(2).0, 2);
+        assert_eq!(storage.get(1).0, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn archetype_storage_get_panic() {
+        let storage: ArchetypeStorage<Position> = ArchetypeStorage::new();
+        storage.get(0);
+    }
+
+    #[test]
+    fn same_components_share_chunk() {
+        let (archetypes, mut positions, mut velocities) = setup();
+        for index in 0..4 {
+            positions.insert(index, Position(index as i32));
+        }
+        velocities.insert(1, Velocity(1));
+        velocities.insert(3, Velocity(3));
+        assert_eq!(archetypes.archetype(0), archetypes.archetype(2));
+        assert_eq!(archetypes.archetype(1), archetypes.archetype(3));
+        assert_ne!(archetypes.archetype(0), archetypes.archetype(1));
+
+        // positions of 1 and 3 are still in the chunk without velocities
+        assert_eq!(layout(&positions).len(), 1);
+        assert!(ChunkedStorage::chunks(&positions).is_none());
+        positions.maintain();
+        velocities.maintain();
+        assert!(ChunkedStorage::chunks(&positions).is_some());
+
+        let chunks = layout(&positions);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].1, &[0, 2]);
+        assert_eq!(chunks[1].0, archetypes.archetype(1));
+        assert_eq!(chunks[1].1, &[1, 3]);
+        assert_eq!(chunks[1].2, &[Position(1), Position(3)]);
+        assert_eq!(layout(&velocities)[0].1, &[1, 3]);
+        assert_eq!(positions.get(3).0, 3);
+    }
+
+    #[test]
+    fn remove_moves_back() {
+        let (archetypes, mut positions, mut velocities) = setup();
+        positions.insert(0, Position(0));
+        positions.insert(1, Position(1));
+        velocities.insert(0, Velocity(0));
+        positions.maintain();
+        velocities.remove(0);
+        positions.maintain();
+
+        assert_eq!(archetypes.archetype(0), archetypes.archetype(1));
+        let chunks = layout(&positions);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].1, &[0, 1]);
+    }
+}
@@ -1,5 +1,13 @@
 use super::*;
 
+mod archetype;
+mod packed;
+mod shared;
 mod vec;
 
+pub use self::archetype::{ArchetypeId, ArchetypeStorage, Archetypes, Chunks};
+pub use self::packed::PackedStorage;
+pub use self::shared::SharedStorage;
 pub use self::vec::VecStorage;
+
+const MISSING_COMPONENT: &str = "No component at the given index";
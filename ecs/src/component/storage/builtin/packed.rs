@@ -0,0 +1,143 @@
+use super::*;
+
+// Components are kept back to back regardless of which entities hold them.
+// Removal moves the last component into the hole, `compact` puts them back
+// in entity order so joins walk memory front to back.
+#[derive(Derivative)]
+#[derivative(Default(new = "true", bound = ""))]
+pub struct PackedStorage<T: Component> {
+    data: Vec<T>,
+    indices: Vec<Index>,
+    slots: Vec<Option<usize>>,
+}
+
+impl<T> PackedStorage<T>
+where
+    T: Component,
+{
+    fn slot(&self, index: Index) -> usize {
+        self.slots
+            .get(index)
+            .and_then(|slot| *slot)
+            .expect(MISSING_COMPONENT)
+    }
+}
+
+impl<T> RawStorage<T> for PackedStorage<T>
+where
+    T: Component,
+{
+    fn get(&self, index: Index) -> &T {
+        &self.data[self.slot(index)]
+    }
+
+    fn contains(&self, index: Index) -> bool {
+        match self.slots.get(index) {
+            Some(&Some(_)) => true,
+            _ => false,
+        }
+    }
+
+    fn get_mut(&mut self, index: Index) -> &mut T {
+        let slot = self.slot(index);
+        &mut self.data[slot]
+    }
+
+    fn insert(&mut self, index: Index, component: T) {
+        if self.contains(index) {
+            let slot = self.slot(index);
+            self.data[slot] = component;
+            return;
+        }
+
+        let len = self.slots.len();
+        if len <= index {
+            self.slots.extend((len..index + 1).map(|_| None));
+        }
+
+        self.slots[index] = Some(self.data.len());
+        self.data.push(component);
+        self.indices.push(index);
+    }
+
+    fn remove(&mut self, index: Index) -> T {
+        let slot = self.slot(index);
+        self.slots[index] = None;
+        self.indices.swap_remove(slot);
+        if let Some(moved) = self.indices.get(slot) {
+            self.slots[*moved] = Some(slot);
+        }
+        self.data.swap_remove(slot)
+    }
+
+    fn compact(&mut self) {
+        let mut components: Vec<(Index, T)> =
+            self.indices.drain(..).zip(self.data.drain(..)).collect();
+        components.sort_unstable_by_key(|&(index, _)| index);
+
+        for (slot, (index, component)) in components.into_iter().enumerate() {
+            self.slots[index] = Some(slot);
+            self.indices.push(index);
+            self.data.push(component);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MyComponent(i32);
+    impl Component for MyComponent {
+        type Storage = PackedStorage<Self>;
+    }
+
+    #[test]
+    fn packed_storage_insert() {
+        let mut storage: PackedStorage<MyComponent> = PackedStorage::new();
+        storage.insert(5, MyComponent(5));
+        storage.insert(2, MyComponent(2));
+        storage.insert(2, MyComponent(3));
+        assert!(!storage.contains(0));
+        assert_eq!(storage.get(2).0, 3);
+        assert_eq!(storage.get(5).0, 5);
+        assert_eq!(storage.data.len(), 2);
+    }
+
+    #[test]
+    fn packed_storage_remove() {
+        let mut storage: PackedStorage<MyComponent> = PackedStorage::new();
+        storage.insert(0, MyComponent(0));
+        storage.insert(1, MyComponent(1));
+        storage.insert(2, MyComponent(2));
+        assert_eq!(storage.remove(0).0, 0);
+        assert!(!storage.contains(0));
+        assert_eq!(storage.get(1).0, 1);
+        assert_eq!(storage.get(2).0, 2);
+        assert_eq!(storage.remove(2).0, 2);
+        assert_eq!(storage.get(1).0, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn packed_storage_get_panic() {
+        let storage: PackedStorage<MyComponent> = PackedStorage::new();
+        storage.get(0);
+    }
+
+    #[test]
+    fn packed_storage_compact() {
+        let mut storage: PackedStorage<MyComponent> = PackedStorage::new();
+        for index in 0..4 {
+            storage.insert(index, MyComponent(index as i32));
+        }
+        storage.remove(0);
+        storage.insert(0, MyComponent(10));
+        assert_eq!(storage.indices, vec![3, 1, 2, 0]);
+
+        storage.compact();
+        assert_eq!(storage.indices, vec![0, 1, 2, 3]);
+        assert_eq!(storage.get(0).0, 10);
+        assert_eq!(storage.get(3).0, 3);
+    }
+}
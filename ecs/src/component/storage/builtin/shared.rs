@@ -0,0 +1,246 @@
+use fxhash::FxHashMap;
+use std::mem;
+use std::sync::Arc;
+
+use super::*;
+
+// Entities can point at the same value, see `MaskedStorage::insert_shared`.
+// `get_mut` clones a value that is still shared before handing it out.
+// Values dropped by the storage only see `on_remove` once the last index
+// holding them lets go.
+#[derive(Derivative)]
+#[derivative(Default(new = "true", bound = ""))]
+pub struct SharedStorage<T: Component> {
+    vec: Vec<Option<Arc<T>>>,
+}
+
+impl<T> SharedStorage<T>
+where
+    T: Component,
+{
+    pub fn shared(&self, index: Index) -> &Arc<T> {
+        self.vec[index].as_ref().expect(MISSING_COMPONENT)
+    }
+
+    pub fn insert_shared(&mut self, index: Index, value: Arc<T>) {
+        let len = self.vec.len();
+        if len <= index {
+            self.vec.extend((len..index + 1).map(|_| None));
+        }
+
+        self.vec[index] = Some(value);
+    }
+
+    // Returns the old value unless other indices still share it.
+    pub fn replace_shared(&mut self, index: Index, value: Arc<T>) -> Option<T> {
+        let slot = self.vec[index].as_mut().expect(MISSING_COMPONENT);
+        Arc::try_unwrap(mem::replace(slot, value)).ok()
+    }
+}
+
+impl<T> RawStorage<T> for SharedStorage<T>
+where
+    T: Component + Clone,
+{
+    fn get(&self, index: Index) -> &T {
+        self.shared(index)
+    }
+
+    fn contains(&self, index: Index) -> bool {
+        match self.vec.get(index) {
+            Some(&Some(_)) => true,
+            _ => false,
+        }
+    }
+
+    fn get_mut(&mut self, index: Index) -> &mut T {
+        Arc::make_mut(self.vec[index].as_mut().expect(MISSING_COMPONENT))
+    }
+
+    fn insert(&mut self, index: Index, component: T) {
+        self.insert_shared(index, Arc::new(component));
+    }
+
+    fn remove(&mut self, index: Index) -> T {
+        let value = self.vec[index].take().expect(MISSING_COMPONENT);
+        Arc::try_unwrap(value).unwrap_or_else(|value| (*value).clone())
+    }
+
+    fn discard(&mut self, index: Index) -> Option<T> {
+        let value = self.vec[index].take().expect(MISSING_COMPONENT);
+        Arc::try_unwrap(value).ok()
+    }
+
+    fn replace(&mut self, index: Index, component: T) -> Option<T> {
+        self.replace_shared(index, Arc::new(component))
+    }
+}
+
+impl<T> MaskedStorage<T>
+where
+    T: Component<Storage = SharedStorage<T>> + Clone,
+{
+    pub fn shared(&self, index: Index) -> Option<&Arc<T>> {
+        if self.contains(index) {
+            Some(self.1.shared(index))
+        } else {
+            None
+        }
+    }
+
+    // `on_insert` doesn't run here, it would need a copy of its own to
+    // mutate. The replaced value gets `on_remove` like in `insert`.
+    pub fn insert_shared(&mut self, index: Index, value: Arc<T>) {
+        if self.0.insert(index) {
+            self.1.insert_shared(index, value);
+            self.2.single_write(ComponentEvent::Inserted(index));
+        } else {
+            if let Some(mut replaced) = self.1.replace_shared(index, value) {
+                replaced.on_remove(index, &self.3);
+            }
+            self.2.single_write(ComponentEvent::Modified(index));
+        }
+    }
+
+    // Items of `join` grouped by the value their entity shares, groups are
+    // ordered by their first entity.
+    pub fn group_by<J: Join>(&self, mut join: J) -> Vec<(&T, Vec<J::Item>)> {
+        let mut mask = join.open();
+        mask.intersect_with(&self.0);
+
+        let mut groups: Vec<(&T, Vec<J::Item>)> = Vec::new();
+        let mut lookup: FxHashMap<*const T, usize> = Default::default();
+        for index in mask.iter() {
+            let value: &T = self.1.shared(index);
+            let group = *lookup.entry(value as *const T).or_insert_with(|| {
+                groups.push((value, Vec::new()));
+                groups.len() - 1
+            });
+            // every index of the mask is visited once
+            groups[group].1.push(unsafe { join.get(index) });
+        }
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Profile(i32);
+    impl Component for Profile {
+        type Storage = SharedStorage<Self>;
+    }
+
+    #[test]
+    fn shared_insert() {
+        let mut storage = <MaskedStorage<Profile>>::new();
+        let profile = Arc::new(Profile(1));
+        storage.insert_shared(0, profile.clone());
+        storage.insert_shared(1, profile.clone());
+
+        assert_eq!(Arc::strong_count(&profile), 3);
+        assert!(Arc::ptr_eq(storage.shared(0).unwrap(), storage.shared(1).unwrap()));
+    }
+
+    #[test]
+    fn copy_on_write() {
+        let mut storage = <MaskedStorage<Profile>>::new();
+        let profile = Arc::new(Profile(1));
+        storage.insert_shared(0, profile.clone());
+        storage.insert_shared(1, profile.clone());
+
+        storage.get_mut(0).unwrap().0 = 2;
+        assert_eq!(storage.get(0), Some(&Profile(2)));
+        assert_eq!(storage.get(1), Some(&Profile(1)));
+        assert_eq!(Arc::strong_count(&profile), 2);
+    }
+
+    #[test]
+    fn remove() {
+        let mut storage = <MaskedStorage<Profile>>::new();
+        let profile = Arc::new(Profile(1));
+        storage.insert_shared(0, profile.clone());
+        assert_eq!(storage.remove(0), Some(Profile(1)));
+        assert_eq!(Arc::strong_count(&profile), 1);
+    }
+
+    #[derive(Clone)]
+    struct Hooked;
+    impl Component for Hooked {
+        type Storage = SharedStorage<Self>;
+
+        fn on_remove(&mut self, entity: Index, commands: &Commands) {
+            commands.destroy(entity);
+        }
+    }
+
+    #[test]
+    fn replace_runs_on_remove() {
+        let mut storage = <MaskedStorage<Hooked>>::new();
+        let hooked = Arc::new(Hooked);
+        storage.insert_shared(0, Arc::new(Hooked));
+        storage.insert_shared(0, hooked.clone());
+        assert_eq!(storage.commands().take().len(), 1);
+
+        // still held by `hooked`
+        storage.insert_shared(0, Arc::new(Hooked));
+        assert!(storage.commands().is_empty());
+        assert_eq!(Arc::strong_count(&hooked), 1);
+    }
+
+    // Cloning panics, so anything that un-shares a value fails the test.
+    struct Config;
+    impl Clone for Config {
+        fn clone(&self) -> Self {
+            panic!("Config cloned!");
+        }
+    }
+    impl Component for Config {
+        type Storage = SharedStorage<Self>;
+    }
+
+    #[test]
+    fn drop_without_cloning() {
+        let mut storage = <MaskedStorage<Config>>::new();
+        let config = Arc::new(Config);
+        for index in 0..4 {
+            storage.insert_shared(index, config.clone());
+        }
+
+        storage.insert(0, Config);
+        storage.insert_shared(1, Arc::new(Config));
+        storage.retain(|index, _| index != 2);
+        storage.discard(3);
+        assert_eq!(Arc::strong_count(&config), 1);
+
+        storage.insert_shared(2, config.clone());
+        storage.clear();
+        assert_eq!(Arc::strong_count(&config), 1);
+    }
+
+    #[test]
+    fn group_by() {
+        let mut storage = <MaskedStorage<Profile>>::new();
+        let first = Arc::new(Profile(1));
+        let second = Arc::new(Profile(1));
+        storage.insert_shared(0, first.clone());
+        storage.insert_shared(1, second.clone());
+        storage.insert_shared(2, first.clone());
+        storage.insert(3, Profile(3));
+
+        let mut other = <MaskedStorage<Profile>>::new();
+        for index in 0..3 {
+            other.insert(index, Profile(index as i32));
+        }
+
+        let groups: Vec<(i32, Vec<i32>)> = storage
+            .group_by((&other,))
+            .into_iter()
+            .map(|(value, items)| (value.0, items.into_iter().map(|(c,)| c.0).collect()))
+            .collect();
+        // equal values are only grouped when they are the same allocation
+        assert_eq!(groups, vec![(1, vec![0, 2]), (1, vec![1])]);
+    }
+}
@@ -3,7 +3,7 @@ use super::*;
 #[derive(Derivative)]
 #[derivative(Default(new = "true", bound = ""))]
 pub struct VecStorage<T: Component> {
-    vec: Vec<T>,
+    vec: Vec<Option<T>>,
 }
 
 impl<T> RawStorage<T> for VecStorage<T>
@@ -11,30 +11,31 @@ where
     T: Component,
 {
     fn get(&self, index: Index) -> &T {
-        &self.vec[index]
+        self.vec[index].as_ref().expect(MISSING_COMPONENT)
     }
 
     fn contains(&self, index: Index) -> bool {
         match self.vec.get(index) {
-            Some(_) => true,
-            None => false,
+            Some(&Some(_)) => true,
+            _ => false,
         }
     }
 
     fn get_mut(&mut self, index: Index) -> &mut T {
-        &mut self.vec[index]
+        self.vec[index].as_mut().expect(MISSING_COMPONENT)
     }
 
     fn insert(&mut self, index: Index, component: T) {
-        if self.vec.capacity() <= index {
-            self.vec.reserve(index + 10)
+        let len = self.vec.len();
+        if len <= index {
+            self.vec.extend((len..index + 1).map(|_| None));
         }
 
-        self.vec.insert(index, component)
+        self.vec[index] = Some(component);
     }
 
     fn remove(&mut self, index: Index) -> T {
-        self.vec.remove(index)
+        self.vec[index].take().expect(MISSING_COMPONENT)
     }
 }
 
@@ -69,6 +70,17 @@ mod tests {
         assert!(!storage.contains(0));
     }
 
+    #[test]
+    fn vec_storage_insert_sparse() {
+        let mut storage: VecStorage<MyOtherComponent> = VecStorage::new();
+        storage.insert(5, MyOtherComponent(5));
+        storage.insert(2, MyOtherComponent(2));
+        assert!(!storage.contains(0));
+        assert_eq!(storage.get(2).0, 2);
+        assert_eq!(storage.get(5).0, 5);
+    }
+
+
     #[test]
     fn vec_storage_get() {
         let mut storage: VecStorage<MyComponent> = VecStorage::new();
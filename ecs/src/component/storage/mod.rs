@@ -1,13 +1,19 @@
 use bit_set::BitSet;
 use std::default::Default;
 use std::mem;
+use std::vec::IntoIter;
 
-use super::super::join::Join;
+use super::super::command::Commands;
+use super::super::event::{EventChannel, ReaderId};
+use super::super::join::{ChunkedJoin, Join};
 use super::Component;
 
 mod builtin;
 
-pub use self::builtin::VecStorage;
+pub use self::builtin::{
+    ArchetypeId, ArchetypeStorage, Archetypes, Chunks, PackedStorage, SharedStorage,
+    VecStorage,
+};
 
 pub type Index = usize;
 
@@ -17,22 +23,113 @@ pub trait RawStorage<T: Component>: Default + Sized {
     fn get_mut(&mut self, index: Index) -> &mut T;
     fn insert(&mut self, index: Index, component: T);
     fn remove(&mut self, index: Index) -> T;
+
+    // Removes a component the caller is going to drop. Storages sharing
+    // values return `None` while other indices still hold it, instead of
+    // copying it just to drop the copy.
+    fn discard(&mut self, index: Index) -> Option<T> {
+        Some(self.remove(index))
+    }
+
+    // Puts `component` in place of the current one, which is returned the
+    // same way as by `discard`.
+    fn replace(&mut self, index: Index, component: T) -> Option<T> {
+        Some(mem::replace(self.get_mut(index), component))
+    }
+
+    // Lays components out in index order, for storages where that can drift.
+    fn compact(&mut self) {}
+
+    // Storages laid out by archetype return themselves here.
+    fn chunked(&self) -> Option<&ChunkedStorage<T>> {
+        None
+    }
+
+    fn chunked_mut(&mut self) -> Option<&mut ChunkedStorage<T>> {
+        None
+    }
+
+    // Hands over the archetypes shared by the storages of a world.
+    fn attach(&mut self, _archetypes: &Archetypes) {}
+
+    // Run by `World::maintain`, for storages that lay components out lazily.
+    fn maintain(&mut self) {}
 }
 
-pub struct MaskedStorage<T: Component>(BitSet, T::Storage);
+// Storages laid out by archetype can be joined chunk by chunk.
+pub trait ChunkedStorage<T: Component> {
+    // `None` if the layout is out of date.
+    fn chunks(&self) -> Option<Chunks>;
+
+    // A row of one of the chunks returned by `chunks`.
+    fn row(&self, archetype: ArchetypeId, row: usize) -> &T;
+
+    // Same as `row`, along with the index the component belongs to.
+    fn row_mut(&mut self, archetype: ArchetypeId, row: usize) -> (Index, &mut T);
+}
+
+const NOT_CHUNKED: &str = "Storage isn't laid out in chunks";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ComponentEvent {
+    Inserted(Index),
+    Modified(Index),
+    Removed(Index),
+}
+
+pub struct MaskedStorage<T: Component>(
+    BitSet,
+    T::Storage,
+    EventChannel<ComponentEvent>,
+    Commands,
+);
 
 impl<T> MaskedStorage<T>
 where
     T: Component,
 {
     pub fn new() -> Self {
-        MaskedStorage(Default::default(), Default::default())
+        Self::with_commands(Default::default())
+    }
+
+    // Hooks queue into `commands`, `new` gives the storage a queue of its own.
+    pub(crate) fn with_commands(commands: Commands) -> Self {
+        MaskedStorage(
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            commands,
+        )
+    }
+
+    pub fn commands(&self) -> &Commands {
+        &self.3
+    }
+
+    pub fn track(&mut self) -> ReaderId {
+        self.2.register_reader()
+    }
+
+    pub fn events(&self, reader: &mut ReaderId) -> &[ComponentEvent] {
+        self.2.read(reader)
     }
 
     pub fn entities(&self) -> BitSet {
         self.0.clone()
     }
 
+    pub(crate) fn mask(&self) -> &BitSet {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     pub fn contains(&self, index: Index) -> bool {
         self.0.contains(index)
     }
@@ -47,6 +144,7 @@ where
 
     pub fn get_mut(&mut self, index: Index) -> Option<&mut T> {
         if self.contains(index) {
+            self.2.single_write(ComponentEvent::Modified(index));
             Some(self.1.get_mut(index))
         } else {
             None
@@ -54,19 +152,481 @@ where
     }
 
     pub fn insert(&mut self, index: Index, mut component: T) {
+        component.on_insert(index, &self.3);
         if self.contains(index) {
-            mem::swap(&mut component, { self.1.get_mut(index) })
+            if let Some(mut replaced) = self.1.replace(index, component) {
+                replaced.on_remove(index, &self.3);
+            }
+            self.2.single_write(ComponentEvent::Modified(index));
         } else {
             self.0.insert(index);
             self.1.insert(index, component);
+            self.2.single_write(ComponentEvent::Inserted(index));
+        }
+    }
+
+    pub fn clear(&mut self) {
+        let indices = mem::replace(&mut self.0, BitSet::new());
+        for index in indices.iter() {
+            self.2.single_write(ComponentEvent::Removed(index));
+            if let Some(mut component) = self.1.discard(index) {
+                component.on_remove(index, &self.3);
+            }
+        }
+    }
+
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Index, &T) -> bool,
+    {
+        let indices: Vec<Index> = self.0.iter().collect();
+        for index in indices {
+            if !f(index, self.1.get(index)) {
+                self.discard(index);
+            }
         }
     }
 
+    // Like `retain`, kept components are reported as modified.
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Index, &mut T) -> bool,
+    {
+        let indices: Vec<Index> = self.0.iter().collect();
+        for index in indices {
+            if f(index, self.1.get_mut(index)) {
+                self.2.single_write(ComponentEvent::Modified(index));
+            } else {
+                self.discard(index);
+            }
+        }
+    }
+
+    pub fn drain(&mut self) -> Drain<T> {
+        let indices: Vec<Index> = self.0.iter().collect();
+        Drain {
+            storage: self,
+            indices: indices.into_iter(),
+        }
+    }
+
+    pub fn extend<I>(&mut self, components: I)
+    where
+        I: IntoIterator<Item = (Index, T)>,
+    {
+        // same as `insert`, with the new bits merged into the mask at once
+        let mut inserted = BitSet::new();
+        for (index, mut component) in components {
+            component.on_insert(index, &self.3);
+            if self.contains(index) || inserted.contains(index) {
+                if let Some(mut replaced) = self.1.replace(index, component) {
+                    replaced.on_remove(index, &self.3);
+                }
+                self.2.single_write(ComponentEvent::Modified(index));
+            } else {
+                inserted.insert(index);
+                self.1.insert(index, component);
+                self.2.single_write(ComponentEvent::Inserted(index));
+            }
+        }
+        self.0.union_with(&inserted);
+    }
+
+    pub fn compact(&mut self) {
+        self.1.compact();
+    }
+
+    pub(crate) fn chunks(&self) -> Option<Chunks> {
+        self.1.chunked().and_then(|storage| storage.chunks())
+    }
+
+    pub(crate) fn row(&self, archetype: ArchetypeId, row: usize) -> &T {
+        self.1.chunked().expect(NOT_CHUNKED).row(archetype, row)
+    }
+
+    pub(crate) fn row_mut(&mut self, archetype: ArchetypeId, row: usize) -> &mut T {
+        let (index, component) = self
+            .1
+            .chunked_mut()
+            .expect(NOT_CHUNKED)
+            .row_mut(archetype, row);
+        self.2.single_write(ComponentEvent::Modified(index));
+        component
+    }
+
+    pub(crate) fn attach(&mut self, archetypes: &Archetypes) {
+        self.1.attach(archetypes);
+    }
+
+    pub(crate) fn maintain(&mut self) {
+        self.1.maintain();
+    }
+
     pub fn remove(&mut self, index: Index) -> Option<T> {
         if self.contains(index) {
+            self.0.remove(index);
+            self.2.single_write(ComponentEvent::Removed(index));
+            let mut component = self.1.remove(index);
+            component.on_remove(index, &self.3);
+            Some(component)
+        } else {
+            None
+        }
+    }
+
+    // Like `remove` for callers that drop the component anyway.
+    pub(crate) fn discard(&mut self, index: Index) {
+        if self.0.remove(index) {
+            self.2.single_write(ComponentEvent::Removed(index));
+            if let Some(mut component) = self.1.discard(index) {
+                component.on_remove(index, &self.3);
+            }
+        }
+    }
+
+    // `put`, `take` and `forget` skip the hooks, for moving components
+    // between worlds and rolling back. Change events are still written.
+    pub(crate) fn put(&mut self, index: Index, component: T) {
+        if self.0.insert(index) {
+            self.1.insert(index, component);
+            self.2.single_write(ComponentEvent::Inserted(index));
+        } else {
+            self.1.replace(index, component);
+            self.2.single_write(ComponentEvent::Modified(index));
+        }
+    }
+
+    pub(crate) fn take(&mut self, index: Index) -> Option<T> {
+        if self.0.remove(index) {
+            self.2.single_write(ComponentEvent::Removed(index));
             Some(self.1.remove(index))
         } else {
             None
         }
     }
+
+    pub(crate) fn forget(&mut self, index: Index) {
+        if self.0.remove(index) {
+            self.2.single_write(ComponentEvent::Removed(index));
+            self.1.discard(index);
+        }
+    }
+}
+
+// Removes components as it goes, whatever is left is dropped with it.
+pub struct Drain<'a, T: 'a + Component> {
+    storage: &'a mut MaskedStorage<T>,
+    indices: IntoIter<Index>,
+}
+
+impl<'a, T> Iterator for Drain<'a, T>
+where
+    T: Component,
+{
+    type Item = (Index, T);
+
+    fn next(&mut self) -> Option<(Index, T)> {
+        let storage = &mut self.storage;
+        self.indices
+            .next()
+            .map(|index| (index, storage.remove(index).expect("Component not found!")))
+    }
+}
+
+impl<'a, T> Drop for Drain<'a, T>
+where
+    T: Component,
+{
+    fn drop(&mut self) {
+        for _ in self {}
+    }
+}
+
+impl<'a, T> Join for &'a MaskedStorage<T>
+where
+    T: Component,
+{
+    type Item = &'a T;
+
+    fn open(&self) -> BitSet {
+        self.entities()
+    }
+
+    unsafe fn get(&mut self, index: Index) -> &'a T {
+        let storage: &'a MaskedStorage<T> = *self;
+        storage.get(index).expect("Component not found!")
+    }
+
+    fn chunked(&mut self) -> Option<&mut ChunkedJoin<Item = &'a T>> {
+        Some(self)
+    }
+}
+
+impl<'a, T> ChunkedJoin for &'a MaskedStorage<T>
+where
+    T: Component,
+{
+    type Item = &'a T;
+
+    fn chunks(&mut self) -> Option<Chunks> {
+        MaskedStorage::chunks(self)
+    }
+
+    unsafe fn get_row(&mut self, archetype: ArchetypeId, row: usize) -> &'a T {
+        let storage: &'a MaskedStorage<T> = *self;
+        storage.row(archetype, row)
+    }
+}
+
+impl<'a, T> Join for &'a mut MaskedStorage<T>
+where
+    T: Component,
+{
+    type Item = &'a mut T;
+
+    fn open(&self) -> BitSet {
+        self.entities()
+    }
+
+    unsafe fn get(&mut self, index: Index) -> &'a mut T {
+        let component: *mut T = self.get_mut(index).expect("Component not found!");
+        // callers request an index once, so the references never alias
+        &mut *component
+    }
+
+    fn chunked(&mut self) -> Option<&mut ChunkedJoin<Item = &'a mut T>> {
+        Some(self)
+    }
+}
+
+impl<'a, T> ChunkedJoin for &'a mut MaskedStorage<T>
+where
+    T: Component,
+{
+    type Item = &'a mut T;
+
+    fn chunks(&mut self) -> Option<Chunks> {
+        MaskedStorage::chunks(self)
+    }
+
+    unsafe fn get_row(&mut self, archetype: ArchetypeId, row: usize) -> &'a mut T {
+        let component: *mut T = self.row_mut(archetype, row);
+        &mut *component
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::entity::Entity;
+    use super::*;
+
+    struct MyComponent(i32);
+    impl Component for MyComponent {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[test]
+    fn remove() {
+        let mut storage = <MaskedStorage<MyComponent>>::new();
+        storage.insert(0, MyComponent(0));
+        assert_eq!(storage.remove(0).map(|c| c.0), Some(0));
+        assert!(!storage.contains(0));
+        assert!(storage.remove(0).is_none());
+    }
+
+    #[test]
+    fn len() {
+        let mut storage = <MaskedStorage<MyComponent>>::new();
+        assert!(storage.is_empty());
+        storage.insert(0, MyComponent(0));
+        storage.insert(3, MyComponent(3));
+        assert_eq!(storage.len(), 2);
+        assert!(!storage.is_empty());
+    }
+
+    #[test]
+    fn extend() {
+        let mut storage = <MaskedStorage<MyComponent>>::new();
+        storage.insert(1, MyComponent(1));
+        let mut reader = storage.track();
+        storage.extend(vec![
+            (1, MyComponent(10)),
+            (4, MyComponent(4)),
+            (4, MyComponent(40)),
+        ]);
+
+        assert_eq!(storage.len(), 2);
+        assert_eq!(storage.get(1).map(|c| c.0), Some(10));
+        assert_eq!(storage.get(4).map(|c| c.0), Some(40));
+        assert_eq!(
+            storage.events(&mut reader),
+            &[
+                ComponentEvent::Modified(1),
+                ComponentEvent::Inserted(4),
+                ComponentEvent::Modified(4),
+            ]
+        );
+    }
+
+    #[test]
+    fn clear() {
+        let mut storage = <MaskedStorage<MyComponent>>::new();
+        storage.insert(0, MyComponent(0));
+        storage.insert(3, MyComponent(3));
+        let mut reader = storage.track();
+        storage.clear();
+
+        assert!(storage.is_empty());
+        assert!(!storage.contains(3));
+        assert_eq!(
+            storage.events(&mut reader),
+            &[ComponentEvent::Removed(0), ComponentEvent::Removed(3)]
+        );
+        // still usable
+        storage.insert(3, MyComponent(4));
+        assert_eq!(storage.get(3).map(|c| c.0), Some(4));
+    }
+
+    #[test]
+    fn retain() {
+        let mut storage = <MaskedStorage<MyComponent>>::new();
+        for index in 0..4 {
+            storage.insert(index, MyComponent(index as i32));
+        }
+        let mut reader = storage.track();
+        storage.retain(|index, component| index % 2 == 0 && component.0 < 2);
+
+        assert_eq!(storage.len(), 1);
+        assert!(storage.contains(0));
+        assert_eq!(
+            storage.events(&mut reader),
+            &[
+                ComponentEvent::Removed(1),
+                ComponentEvent::Removed(2),
+                ComponentEvent::Removed(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn retain_mut() {
+        let mut storage = <MaskedStorage<MyComponent>>::new();
+        for index in 0..4 {
+            storage.insert(index, MyComponent(index as i32));
+        }
+        let mut reader = storage.track();
+        storage.retain_mut(|index, component| {
+            component.0 *= 10;
+            index % 2 == 0
+        });
+
+        assert_eq!(storage.len(), 2);
+        assert_eq!(storage.get(2).map(|c| c.0), Some(20));
+        assert!(!storage.contains(1));
+        assert_eq!(
+            storage.events(&mut reader),
+            &[
+                ComponentEvent::Modified(0),
+                ComponentEvent::Removed(1),
+                ComponentEvent::Modified(2),
+                ComponentEvent::Removed(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn drain() {
+        let mut storage = <MaskedStorage<MyComponent>>::new();
+        storage.insert(1, MyComponent(1));
+        storage.insert(2, MyComponent(2));
+
+        let drained: Vec<(Index, i32)> = storage.drain().map(|(i, c)| (i, c.0)).collect();
+        assert_eq!(drained, vec![(1, 1), (2, 2)]);
+        assert!(storage.is_empty());
+    }
+
+    #[test]
+    fn drain_dropped() {
+        let mut storage = <MaskedStorage<MyComponent>>::new();
+        storage.insert(1, MyComponent(1));
+        storage.insert(2, MyComponent(2));
+
+        storage.drain().next();
+        assert!(storage.is_empty());
+    }
+
+    #[test]
+    fn join() {
+        let mut storage = <MaskedStorage<MyComponent>>::new();
+        storage.insert(0, MyComponent(0));
+        storage.insert(2, MyComponent(2));
+
+        for (component,) in (&mut storage,).join() {
+            component.0 += 1;
+        }
+
+        let values: Vec<i32> = (&storage,).join().map(|(c,)| c.0).collect();
+        assert_eq!(values, vec![1, 3]);
+    }
+
+    #[test]
+    fn events() {
+        let mut storage = <MaskedStorage<MyComponent>>::new();
+        storage.insert(0, MyComponent(0));
+        let mut reader = storage.track();
+
+        storage.insert(1, MyComponent(1));
+        storage.insert(1, MyComponent(2));
+        storage.get_mut(0).unwrap().0 = 3;
+        storage.remove(0);
+        // nothing happens
+        storage.remove(0);
+
+        assert_eq!(
+            storage.events(&mut reader),
+            &[
+                ComponentEvent::Inserted(1),
+                ComponentEvent::Modified(1),
+                ComponentEvent::Modified(0),
+                ComponentEvent::Removed(0),
+            ]
+        );
+    }
+
+    struct Hooked;
+    impl Component for Hooked {
+        type Storage = VecStorage<Self>;
+
+        fn on_insert(&mut self, entity: Entity, commands: &Commands) {
+            commands.insert(entity, MyComponent(1));
+        }
+
+        fn on_remove(&mut self, entity: Entity, commands: &Commands) {
+            commands.remove::<MyComponent>(entity);
+        }
+    }
+
+    #[test]
+    fn hooks() {
+        let mut storage = <MaskedStorage<Hooked>>::new();
+        storage.insert(0, Hooked);
+        assert_eq!(storage.commands().take().len(), 1);
+
+        // replacing removes the old component
+        storage.insert(0, Hooked);
+        assert_eq!(storage.commands().take().len(), 2);
+
+        storage.insert(1, Hooked);
+        storage.insert(2, Hooked);
+        storage.commands().take();
+        storage.remove(0);
+        storage.retain(|index, _| index != 1);
+        storage.drain().count();
+        assert_eq!(storage.commands().take().len(), 3);
+
+        storage.insert(0, Hooked);
+        storage.commands().take();
+        storage.clear();
+        assert_eq!(storage.commands().take().len(), 1);
+    }
 }
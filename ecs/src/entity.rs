@@ -1,6 +1,8 @@
 use super::join::Join;
 use bit_set::BitSet;
+use fxhash::FxHashMap;
 use std::cell::RefCell;
+use std::cmp;
 use std::sync::{Arc, LockResult, Mutex, MutexGuard};
 
 use super::resource::Fetch;
@@ -17,25 +19,67 @@ pub struct EntityStorage {
 
 pub type Entities<'a> = Fetch<'a, EntityStorage>;
 
-const LOCK_POISOINED: &str = "Lock is poisoned!";
+// Old to new ids of entities moved between worlds.
+pub type EntityMap = FxHashMap<Entity, Entity>;
+
+pub(crate) const LOCK_POISOINED: &str = "Lock is poisoned!";
 
 impl EntityStorage {
+    // Locks are always taken in the order limbo, next_id, alive.
     pub fn create(&self) -> Entity {
-        let id = if !self.is_limbo_empty() {
-            // self.limbo.remove(0);
-            self.unlock_mut(self.limbo.lock(), |limbo: &mut Vec<usize>| limbo.remove(0))
-        } else {
-            self.next_id()
-        };
+        let id = self.unlock_mut(self.limbo.lock(), |limbo: &mut Vec<usize>| {
+            limbo.pop().unwrap_or_else(|| self.next_id())
+        });
 
         self.unlock_mut(self.alive.lock(), |alive: &mut BitSet| alive.insert(id));
         id as Entity
     }
 
+    // Creates entities lazily, the locks are only held while creating each one.
+    pub fn create_iter(&self) -> CreateIter {
+        CreateIter { storage: self }
+    }
+
+    pub fn create_many(&self, count: usize) -> Vec<Entity> {
+        let mut entities = Vec::with_capacity(count);
+
+        let limbo = self.limbo.lock().expect(LOCK_POISOINED);
+        let mut limbo = limbo.borrow_mut();
+        let reused = cmp::min(count, limbo.len());
+        let split = limbo.len() - reused;
+        entities.extend(limbo.drain(split..).rev());
+
+        let next_id = self.next_id.lock().expect(LOCK_POISOINED);
+        let mut next_id = next_id.borrow_mut();
+        let start = *next_id;
+        let end = start + (count - reused);
+        *next_id = end;
+        entities.extend(start..end);
+
+        let alive = self.alive.lock().expect(LOCK_POISOINED);
+        let mut alive = alive.borrow_mut();
+        alive.reserve_len(end);
+        for entity in &entities {
+            alive.insert(*entity);
+        }
+
+        entities
+    }
+
+    pub fn alive(&self) -> BitSet {
+        self.unlock(self.alive.lock(), |alive: &BitSet| alive.clone())
+    }
+
     pub fn is_alive(&self, entity: Entity) -> bool {
         self.unlock(self.alive.lock(), |alive: &BitSet| alive.contains(entity))
     }
 
+    pub(crate) fn assert_alive(&self, entity: Entity) {
+        if !self.is_alive(entity) {
+            panic!("Entity {} is not alive.", entity);
+        }
+    }
+
     pub fn destroy(&self, entity: Entity) {
         assert!(self.is_alive(entity), "Can't destroy dead entity!");
         self.unlock_mut(self.limbo.lock(), |limbo: &mut Vec<usize>| {
@@ -44,8 +88,24 @@ impl EntityStorage {
         self.unlock_mut(self.alive.lock(), |alive: &mut BitSet| alive.remove(entity));
     }
 
-    fn is_limbo_empty(&self) -> bool {
-        self.unlock(self.limbo.lock(), |limbo: &Vec<usize>| limbo.is_empty())
+    pub(crate) fn snapshot(&self) -> EntitySnapshot {
+        EntitySnapshot {
+            next_id: self.unlock(self.next_id.lock(), |next_id: &usize| *next_id),
+            alive: self.unlock(self.alive.lock(), |alive: &BitSet| alive.clone()),
+            limbo: self.unlock(self.limbo.lock(), |limbo: &Vec<usize>| limbo.clone()),
+        }
+    }
+
+    pub(crate) fn restore(&self, snapshot: &EntitySnapshot) {
+        self.unlock_mut(self.next_id.lock(), |next_id: &mut usize| {
+            *next_id = snapshot.next_id
+        });
+        self.unlock_mut(self.alive.lock(), |alive: &mut BitSet| {
+            alive.clone_from(&snapshot.alive)
+        });
+        self.unlock_mut(self.limbo.lock(), |limbo: &mut Vec<usize>| {
+            limbo.clone_from(&snapshot.limbo)
+        });
     }
 
     fn next_id(&self) -> usize {
@@ -75,6 +135,55 @@ impl EntityStorage {
     }
 }
 
+impl<'a> Join for Entities<'a> {
+    type Item = Entity;
+
+    fn open(&self) -> BitSet {
+        self.alive()
+    }
+
+    unsafe fn get(&mut self, index: usize) -> Entity {
+        index as Entity
+    }
+}
+
+impl<'a, 'b> Join for &'b Entities<'a> {
+    type Item = Entity;
+
+    fn open(&self) -> BitSet {
+        self.alive()
+    }
+
+    unsafe fn get(&mut self, index: usize) -> Entity {
+        index as Entity
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct EntitySnapshot {
+    next_id: usize,
+    alive: BitSet,
+    limbo: Vec<usize>,
+}
+
+impl EntitySnapshot {
+    pub fn alive(&self) -> &BitSet {
+        &self.alive
+    }
+}
+
+pub struct CreateIter<'a> {
+    storage: &'a EntityStorage,
+}
+
+impl<'a> Iterator for CreateIter<'a> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Entity> {
+        Some(self.storage.create())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -114,6 +223,56 @@ mod tests {
         assert_eq!(entity, new_entity);
     }
 
+    #[test]
+    fn create_iter() {
+        let entity_storage = EntityStorage::new();
+        let entities: Vec<Entity> = entity_storage.create_iter().take(3).collect();
+        assert_eq!(entities, vec![0, 1, 2]);
+        for entity in entities {
+            assert!(entity_storage.is_alive(entity));
+        }
+    }
+
+    #[test]
+    fn create_many() {
+        let entity_storage = EntityStorage::new();
+        let entities = entity_storage.create_many(100);
+        assert_eq!(entities.len(), 100);
+        for entity in entities {
+            assert!(entity_storage.is_alive(entity));
+        }
+        assert!(!entity_storage.is_alive(100));
+        assert_eq!(entity_storage.create(), 100);
+    }
+
+    #[test]
+    fn create_many_re_use_dead_entities() {
+        let entity_storage = EntityStorage::new();
+        let first = entity_storage.create();
+        let second = entity_storage.create();
+        entity_storage.destroy(second);
+        entity_storage.destroy(first);
+
+        let entities = entity_storage.create_many(3);
+        // the most recently destroyed entity is reused first
+        assert_eq!(entities, vec![first, second, 2]);
+    }
+
+    #[test]
+    fn snapshot_restore() {
+        let entity_storage = EntityStorage::new();
+        let first = entity_storage.create();
+        let snapshot = entity_storage.snapshot();
+
+        let second = entity_storage.create();
+        entity_storage.destroy(first);
+        entity_storage.restore(&snapshot);
+
+        assert!(entity_storage.is_alive(first));
+        assert!(!entity_storage.is_alive(second));
+        assert_eq!(entity_storage.create(), second);
+    }
+
     #[test]
     fn unique_ids() {
         let mut entity_storage = EntityStorage::new();
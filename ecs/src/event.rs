@@ -0,0 +1,113 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Handle of a registered reader. Each reader sees every event written
+// after it was registered exactly once.
+#[derive(Debug)]
+pub struct ReaderId {
+    id: usize,
+}
+
+// Events are only buffered while at least one reader is registered and are
+// dropped once every reader has seen them.
+pub struct EventChannel<E> {
+    events: Vec<E>,
+    // absolute position of events[0]
+    offset: usize,
+    cursors: Vec<AtomicUsize>,
+}
+
+impl<E> Default for EventChannel<E> {
+    fn default() -> Self {
+        EventChannel {
+            events: Vec::new(),
+            offset: 0,
+            cursors: Vec::new(),
+        }
+    }
+}
+
+impl<E> EventChannel<E> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn register_reader(&mut self) -> ReaderId {
+        let id = self.cursors.len();
+        self.cursors
+            .push(AtomicUsize::new(self.offset + self.events.len()));
+        ReaderId { id }
+    }
+
+    pub fn single_write(&mut self, event: E) {
+        if self.cursors.is_empty() {
+            return;
+        }
+
+        self.trim();
+        self.events.push(event);
+    }
+
+    pub fn read(&self, reader: &mut ReaderId) -> &[E] {
+        let end = self.offset + self.events.len();
+        let cursor = self.cursors[reader.id].swap(end, Ordering::Relaxed);
+        &self.events[cursor - self.offset..]
+    }
+
+    fn trim(&mut self) {
+        let min = self.cursors
+            .iter()
+            .map(|cursor| cursor.load(Ordering::Relaxed))
+            .min()
+            .unwrap_or(self.offset);
+        if min > self.offset {
+            self.events.drain(..min - self.offset);
+            self.offset = min;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_readers() {
+        let mut channel = EventChannel::new();
+        channel.single_write(1);
+        let mut reader = channel.register_reader();
+        assert!(channel.read(&mut reader).is_empty());
+    }
+
+    #[test]
+    fn read_once() {
+        let mut channel = EventChannel::new();
+        let mut reader = channel.register_reader();
+        channel.single_write(1);
+        channel.single_write(2);
+        assert_eq!(channel.read(&mut reader), &[1, 2]);
+        assert!(channel.read(&mut reader).is_empty());
+    }
+
+    #[test]
+    fn multiple_readers() {
+        let mut channel = EventChannel::new();
+        let mut first = channel.register_reader();
+        let mut second = channel.register_reader();
+        channel.single_write(1);
+        assert_eq!(channel.read(&mut first), &[1]);
+        channel.single_write(2);
+        assert_eq!(channel.read(&mut first), &[2]);
+        assert_eq!(channel.read(&mut second), &[1, 2]);
+    }
+
+    #[test]
+    fn trim_read_events() {
+        let mut channel = EventChannel::new();
+        let mut reader = channel.register_reader();
+        channel.single_write(1);
+        channel.read(&mut reader);
+        channel.single_write(2);
+        assert_eq!(channel.events.len(), 1);
+        assert_eq!(channel.read(&mut reader), &[2]);
+    }
+}
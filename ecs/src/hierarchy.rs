@@ -0,0 +1,321 @@
+use fxhash::FxHashMap;
+
+use super::command::Commands;
+use super::component::storage::{ComponentEvent, MaskedStorage, VecStorage};
+use super::component::Component;
+use super::entity::{Entities, Entity, EntityStorage};
+use super::event::ReaderId;
+use super::resource::{Fetch, FetchMut};
+use super::system::System;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Parent(pub Entity);
+
+impl Component for Parent {
+    type Storage = VecStorage<Self>;
+}
+
+// Mirror of the `Parent` storage, kept up to date by `maintain`.
+pub struct Hierarchy {
+    parents: FxHashMap<Entity, Entity>,
+    children: FxHashMap<Entity, Vec<Entity>>,
+    reader: ReaderId,
+}
+
+impl Hierarchy {
+    pub fn new(storage: &mut MaskedStorage<Parent>) -> Self {
+        let mut hierarchy = Hierarchy {
+            parents: Default::default(),
+            children: Default::default(),
+            reader: storage.track(),
+        };
+
+        let mut cycles = Vec::new();
+        for entity in storage.mask().iter() {
+            let parent = storage.get(entity).expect("Parent not found!").0;
+            if !hierarchy.link(entity, parent) {
+                cycles.push(entity);
+            }
+        }
+        for entity in cycles {
+            storage.remove(entity);
+        }
+
+        hierarchy
+    }
+
+    pub fn parent(&self, entity: Entity) -> Option<Entity> {
+        self.parents.get(&entity).cloned()
+    }
+
+    pub fn children(&self, entity: Entity) -> &[Entity] {
+        self.children
+            .get(&entity)
+            .map(|children| children.as_slice())
+            .unwrap_or(&[])
+    }
+
+    // Depth-first, parents before their children.
+    pub fn descendants(&self, entity: Entity) -> Vec<Entity> {
+        let mut result = Vec::new();
+        let mut stack: Vec<Entity> = self.children(entity).iter().rev().cloned().collect();
+        while let Some(current) = stack.pop() {
+            result.push(current);
+            stack.extend(self.children(current).iter().rev());
+        }
+        result
+    }
+
+    // Every entity in the hierarchy, each root followed by its descendants.
+    pub fn all(&self) -> Vec<Entity> {
+        let mut roots: Vec<Entity> = self.children
+            .keys()
+            .filter(|entity| !self.parents.contains_key(entity))
+            .cloned()
+            .collect();
+        roots.sort();
+
+        let mut result = Vec::new();
+        for root in roots {
+            result.push(root);
+            result.extend(self.descendants(root));
+        }
+        result
+    }
+
+    // Parents that would close a cycle are removed, and entities whose parent
+    // died without going through `World::destroy` are destroyed along with
+    // their descendants. Both go through `commands`, so they take effect on
+    // the next `World::maintain`.
+    pub fn maintain(
+        &mut self,
+        entities: &EntityStorage,
+        storage: &MaskedStorage<Parent>,
+        commands: &Commands,
+    ) {
+        for event in storage.events(&mut self.reader) {
+            match *event {
+                ComponentEvent::Inserted(entity) | ComponentEvent::Modified(entity) => {
+                    match storage.get(entity) {
+                        Some(parent) => if !self.link(entity, parent.0) {
+                            commands.remove::<Parent>(entity);
+                        },
+                        None => self.unlink(entity),
+                    }
+                }
+                ComponentEvent::Removed(entity) => self.unlink(entity),
+            }
+        }
+
+        let mut orphans: Vec<Entity> = self.parents
+            .iter()
+            .filter(|&(_, parent)| !entities.is_alive(*parent))
+            .map(|(child, _)| *child)
+            .collect();
+        orphans.sort();
+
+        for orphan in orphans {
+            // World::destroy takes the descendants with it
+            self.unlink(orphan);
+            commands.destroy(orphan);
+        }
+
+        let dead: Vec<Entity> = self.children
+            .keys()
+            .filter(|entity| !entities.is_alive(**entity))
+            .cloned()
+            .collect();
+        for entity in dead {
+            self.children.remove(&entity);
+        }
+    }
+
+    // Returns false, leaving the child unlinked, if it would become its own
+    // ancestor.
+    fn link(&mut self, child: Entity, parent: Entity) -> bool {
+        if self.parent(child) == Some(parent) {
+            return true;
+        }
+
+        self.unlink(child);
+        let mut ancestor = Some(parent);
+        while let Some(current) = ancestor {
+            if current == child {
+                return false;
+            }
+            ancestor = self.parent(current);
+        }
+
+        self.parents.insert(child, parent);
+        self.children.entry(parent).or_default().push(child);
+        true
+    }
+
+    fn unlink(&mut self, child: Entity) {
+        if let Some(parent) = self.parents.remove(&child) {
+            let empty = match self.children.get_mut(&parent) {
+                Some(children) => {
+                    children.retain(|entity| *entity != child);
+                    children.is_empty()
+                }
+                None => false,
+            };
+
+            if empty {
+                self.children.remove(&parent);
+            }
+        }
+    }
+}
+
+pub struct HierarchySystem;
+
+impl<'a> System<'a> for HierarchySystem {
+    type SystemData = (
+        Entities<'a>,
+        Fetch<'a, MaskedStorage<Parent>>,
+        Fetch<'a, Commands>,
+        FetchMut<'a, Hierarchy>,
+    );
+
+    fn run(&mut self, (entities, parents, commands, mut hierarchy): Self::SystemData) {
+        hierarchy.maintain(&entities, &parents, &commands);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (EntityStorage, MaskedStorage<Parent>, Commands, Hierarchy) {
+        let entities = EntityStorage::new();
+        let mut storage = MaskedStorage::new();
+        let hierarchy = Hierarchy::new(&mut storage);
+        (entities, storage, Commands::default(), hierarchy)
+    }
+
+    #[test]
+    fn children() {
+        let (entities, mut storage, commands, mut hierarchy) = setup();
+        let parent = entities.create();
+        let first = entities.create();
+        let second = entities.create();
+        storage.insert(first, Parent(parent));
+        storage.insert(second, Parent(parent));
+        hierarchy.maintain(&entities, &storage, &commands);
+
+        assert_eq!(hierarchy.children(parent), &[first, second]);
+        assert_eq!(hierarchy.parent(first), Some(parent));
+        assert!(hierarchy.children(first).is_empty());
+    }
+
+    #[test]
+    fn existing_parents() {
+        let entities = EntityStorage::new();
+        let parent = entities.create();
+        let child = entities.create();
+        let mut storage = MaskedStorage::new();
+        storage.insert(child, Parent(parent));
+
+        let hierarchy = Hierarchy::new(&mut storage);
+        assert_eq!(hierarchy.children(parent), &[child]);
+    }
+
+    #[test]
+    fn reparent() {
+        let (entities, mut storage, commands, mut hierarchy) = setup();
+        let first = entities.create();
+        let second = entities.create();
+        let child = entities.create();
+        storage.insert(child, Parent(first));
+        hierarchy.maintain(&entities, &storage, &commands);
+        storage.get_mut(child).unwrap().0 = second;
+        hierarchy.maintain(&entities, &storage, &commands);
+
+        assert!(hierarchy.children(first).is_empty());
+        assert_eq!(hierarchy.children(second), &[child]);
+    }
+
+    #[test]
+    fn remove_parent() {
+        let (entities, mut storage, commands, mut hierarchy) = setup();
+        let parent = entities.create();
+        let child = entities.create();
+        storage.insert(child, Parent(parent));
+        hierarchy.maintain(&entities, &storage, &commands);
+        storage.remove(child);
+        hierarchy.maintain(&entities, &storage, &commands);
+
+        assert_eq!(hierarchy.parent(child), None);
+        assert!(hierarchy.children(parent).is_empty());
+    }
+
+    #[test]
+    fn depth_first_order() {
+        let (entities, mut storage, commands, mut hierarchy) = setup();
+        let root = entities.create();
+        let a = entities.create();
+        let b = entities.create();
+        let a1 = entities.create();
+        let other = entities.create();
+        let other1 = entities.create();
+        storage.insert(a, Parent(root));
+        storage.insert(b, Parent(root));
+        storage.insert(a1, Parent(a));
+        storage.insert(other1, Parent(other));
+        hierarchy.maintain(&entities, &storage, &commands);
+
+        assert_eq!(hierarchy.descendants(root), vec![a, a1, b]);
+        assert_eq!(hierarchy.all(), vec![root, a, a1, b, other, other1]);
+    }
+
+    #[test]
+    fn orphans_queue_destroy() {
+        let (entities, mut storage, commands, mut hierarchy) = setup();
+        let root = entities.create();
+        let child = entities.create();
+        let grandchild = entities.create();
+        storage.insert(child, Parent(root));
+        storage.insert(grandchild, Parent(child));
+        hierarchy.maintain(&entities, &storage, &commands);
+        assert!(commands.is_empty());
+
+        entities.destroy(root);
+        hierarchy.maintain(&entities, &storage, &commands);
+
+        assert_eq!(hierarchy.parent(child), None);
+        assert_eq!(hierarchy.descendants(child), vec![grandchild]);
+        assert_eq!(commands.take().len(), 1);
+        // not queued twice
+        hierarchy.maintain(&entities, &storage, &commands);
+        assert!(commands.is_empty());
+    }
+
+    #[test]
+    fn cycle() {
+        let (entities, mut storage, commands, mut hierarchy) = setup();
+        let first = entities.create();
+        let second = entities.create();
+        storage.insert(first, Parent(second));
+        storage.insert(second, Parent(first));
+        hierarchy.maintain(&entities, &storage, &commands);
+
+        assert_eq!(hierarchy.parent(first), Some(second));
+        assert_eq!(hierarchy.parent(second), None);
+        assert_eq!(commands.take().len(), 1);
+    }
+
+    #[test]
+    fn existing_cycle() {
+        let entities = EntityStorage::new();
+        let first = entities.create();
+        let second = entities.create();
+        let mut storage = MaskedStorage::new();
+        storage.insert(first, Parent(second));
+        storage.insert(second, Parent(first));
+
+        let hierarchy = Hierarchy::new(&mut storage);
+        assert_eq!(hierarchy.parent(first), Some(second));
+        assert!(!storage.contains(second));
+    }
+}
@@ -0,0 +1,219 @@
+use fxhash::FxHashMap;
+use std::any::Any;
+use std::hash::Hash;
+use std::ops::Deref;
+
+use super::component::storage::{ComponentEvent, MaskedStorage};
+use super::component::Component;
+use super::entity::Entity;
+use super::event::ReaderId;
+use super::resource::{Fetch, FetchMut, Resources};
+use super::system::SystemData;
+
+pub trait IndexKey: Any + Clone + Eq + Hash + Send + Sync {}
+
+impl<T> IndexKey for T
+where
+    T: Any + Clone + Eq + Hash + Send + Sync,
+{
+}
+
+// Maps a key derived from every `C` to the entities holding it. It follows
+// the storage through its change events, see `ReadIndex`.
+pub struct ComponentIndex<K: IndexKey, C: Component> {
+    key: fn(&C) -> K,
+    entities: FxHashMap<K, Vec<Entity>>,
+    keys: FxHashMap<Entity, K>,
+    reader: ReaderId,
+}
+
+impl<K, C> ComponentIndex<K, C>
+where
+    K: IndexKey,
+    C: Component,
+{
+    pub fn new(storage: &mut MaskedStorage<C>, key: fn(&C) -> K) -> Self {
+        let mut index = ComponentIndex {
+            key,
+            entities: Default::default(),
+            keys: Default::default(),
+            reader: storage.track(),
+        };
+
+        for entity in storage.mask().iter() {
+            index.update(storage, entity);
+        }
+
+        index
+    }
+
+    pub fn get(&self, key: &K) -> Option<Entity> {
+        self.entities(key).first().cloned()
+    }
+
+    pub fn entities(&self, key: &K) -> &[Entity] {
+        self.entities
+            .get(key)
+            .map(|entities| entities.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn key(&self, entity: Entity) -> Option<&K> {
+        self.keys.get(&entity)
+    }
+
+    pub fn maintain(&mut self, storage: &MaskedStorage<C>) {
+        for event in storage.events(&mut self.reader) {
+            match *event {
+                ComponentEvent::Inserted(entity) | ComponentEvent::Modified(entity) => {
+                    self.update(storage, entity)
+                }
+                ComponentEvent::Removed(entity) => self.remove(entity),
+            }
+        }
+    }
+
+    fn update(&mut self, storage: &MaskedStorage<C>, entity: Entity) {
+        let key = match storage.get(entity) {
+            Some(component) => (self.key)(component),
+            None => return self.remove(entity),
+        };
+
+        if self.keys.get(&entity) == Some(&key) {
+            return;
+        }
+
+        self.remove(entity);
+        self.entities
+            .entry(key.clone())
+            .or_default()
+            .push(entity);
+        self.keys.insert(entity, key);
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        if let Some(key) = self.keys.remove(&entity) {
+            let empty = match self.entities.get_mut(&key) {
+                Some(entities) => {
+                    entities.retain(|e| *e != entity);
+                    entities.is_empty()
+                }
+                None => false,
+            };
+
+            if empty {
+                self.entities.remove(&key);
+            }
+        }
+    }
+}
+
+// Brought up to date when fetched. It keeps the storage borrowed like a
+// `ReadStorage<C>` so lookups can't go stale, which also means it can't be
+// fetched along with a `WriteStorage<C>`.
+pub struct ReadIndex<'a, K: 'a + IndexKey, C: 'a + Component> {
+    index: FetchMut<'a, ComponentIndex<K, C>>,
+    _storage: Fetch<'a, MaskedStorage<C>>,
+}
+
+impl<'a, K, C> Deref for ReadIndex<'a, K, C>
+where
+    K: IndexKey,
+    C: Component,
+{
+    type Target = ComponentIndex<K, C>;
+
+    fn deref(&self) -> &ComponentIndex<K, C> {
+        &self.index
+    }
+}
+
+impl<'a, K, C> SystemData<'a> for ReadIndex<'a, K, C>
+where
+    K: IndexKey,
+    C: Component,
+{
+    fn fetch(res: &'a Resources) -> Self {
+        let mut index = res.fetch_mut::<ComponentIndex<K, C>>();
+        let storage = res.fetch::<MaskedStorage<C>>();
+        index.maintain(&storage);
+        ReadIndex {
+            index,
+            _storage: storage,
+        }
+    }
+}
+
+pub(crate) fn maintain<K, C>(resources: &Resources)
+where
+    K: IndexKey,
+    C: Component,
+{
+    resources
+        .fetch_mut::<ComponentIndex<K, C>>()
+        .maintain(&resources.fetch::<MaskedStorage<C>>());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::component::storage::VecStorage;
+    use super::*;
+
+    struct Name(&'static str);
+    impl Component for Name {
+        type Storage = VecStorage<Self>;
+    }
+
+    fn name(component: &Name) -> &'static str {
+        component.0
+    }
+
+    #[test]
+    fn existing_components() {
+        let mut storage = <MaskedStorage<Name>>::new();
+        storage.insert(0, Name("player"));
+        let index = ComponentIndex::new(&mut storage, name);
+        assert_eq!(index.get(&"player"), Some(0));
+    }
+
+    #[test]
+    fn insert() {
+        let mut storage = <MaskedStorage<Name>>::new();
+        let mut index = ComponentIndex::new(&mut storage, name);
+        storage.insert(0, Name("enemy"));
+        storage.insert(1, Name("enemy"));
+        storage.insert(2, Name("player"));
+        index.maintain(&storage);
+
+        assert_eq!(index.entities(&"enemy"), &[0, 1]);
+        assert_eq!(index.get(&"player"), Some(2));
+        assert_eq!(index.key(2), Some(&"player"));
+        assert_eq!(index.get(&"npc"), None);
+    }
+
+    #[test]
+    fn modify() {
+        let mut storage = <MaskedStorage<Name>>::new();
+        let mut index = ComponentIndex::new(&mut storage, name);
+        storage.insert(0, Name("enemy"));
+        index.maintain(&storage);
+        storage.get_mut(0).unwrap().0 = "player";
+        index.maintain(&storage);
+
+        assert!(index.entities(&"enemy").is_empty());
+        assert_eq!(index.get(&"player"), Some(0));
+    }
+
+    #[test]
+    fn remove() {
+        let mut storage = <MaskedStorage<Name>>::new();
+        let mut index = ComponentIndex::new(&mut storage, name);
+        storage.insert(0, Name("player"));
+        index.maintain(&storage);
+        storage.remove(0);
+        index.maintain(&storage);
+
+        assert_eq!(index.get(&"player"), None);
+        assert_eq!(index.key(0), None);
+    }
+}
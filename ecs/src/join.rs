@@ -1,6 +1,9 @@
 use bit_set::BitSet;
+use std::ops::Range;
 use std::vec::IntoIter;
 
+use super::component::storage::{ArchetypeId, Chunks};
+
 pub trait Join {
     type Item;
 
@@ -13,11 +16,36 @@ pub trait Join {
         JoinIterator::new(self)
     }
 
-    fn get(&self, index: usize) -> Self::Item;
+    // Unsafe because mutable joins hand out `&mut` from a shared borrow.
+    // Callers must request an index in `open` at most once while the items
+    // they got are alive, like `JoinIterator` does.
+    unsafe fn get(&mut self, index: usize) -> Self::Item;
+
+    // Joins that may be laid out by archetype return themselves here, see
+    // `ChunkedJoin`.
+    fn chunked(&mut self) -> Option<&mut ChunkedJoin<Item = Self::Item>> {
+        None
+    }
+}
+
+// Joins where every side is laid out by archetype walk the chunks they share
+// instead of the mask, see `ArchetypeStorage`.
+pub trait ChunkedJoin {
+    type Item;
+
+    // `None` if the sides aren't laid out by archetype right now.
+    fn chunks(&mut self) -> Option<Chunks>;
+
+    // Same contract as `Join::get`, for the rows of the chunks returned by
+    // `chunks`.
+    unsafe fn get_row(&mut self, archetype: ArchetypeId, row: usize) -> Self::Item;
 }
 
 pub struct JoinIterator<T: Join> {
     keys: IntoIter<usize>,
+    chunks: IntoIter<(ArchetypeId, usize)>,
+    // archetype and rows left of the chunk being walked
+    rows: (ArchetypeId, Range<usize>),
     join: T,
 }
 
@@ -25,14 +53,16 @@ impl<T> JoinIterator<T>
 where
     T: Join,
 {
-    pub fn new(join: T) -> Self {
-        let keys = join.open();
-        let mut vec = Vec::new();
-        for key in keys.iter() {
-            vec.push(key);
-        }
+    pub fn new(mut join: T) -> Self {
+        let (keys, chunks) = match join.chunked().and_then(|join| join.chunks()) {
+            Some(chunks) => (Vec::new(), chunks.into_iter().collect()),
+            None => (join.open().iter().collect(), Vec::new()),
+        };
+
         JoinIterator {
-            keys: vec.into_iter(),
+            keys: keys.into_iter(),
+            chunks: chunks.into_iter(),
+            rows: (0, 0..0),
             join,
         }
     }
@@ -45,7 +75,21 @@ where
     type Item = T::Item;
 
     fn next(&mut self) -> Option<T::Item> {
-        self.keys.next().map(|idx| self.join.get(idx))
+        // rows of distinct chunks and keys from a BitSet are visited once
+        loop {
+            if let Some(row) = self.rows.1.next() {
+                let join = self.join.chunked().expect("Join isn't laid out in chunks");
+                return Some(unsafe { join.get_row(self.rows.0, row) });
+            }
+
+            match self.chunks.next() {
+                Some((archetype, len)) => self.rows = (archetype, 0..len),
+                None => break,
+            }
+        }
+
+        let join = &mut self.join;
+        self.keys.next().map(|idx| unsafe { join.get(idx) })
     }
 }
 
@@ -58,18 +102,65 @@ macro_rules! impl_data {
             fn open(&self) -> BitSet {
                 #![allow(unused_variables, non_snake_case)]
 
-                let mut base = BitSet::new();
+                let mut base: Option<BitSet> = None;
                 let ( $($ty, )* ) = self;
-                $( base.intersect_with(&$ty.open()); )*
-                base
+                $(
+                    let open = $ty.open();
+                    base = Some(match base {
+                        Some(mut base) => {
+                            base.intersect_with(&open);
+                            base
+                        }
+                        None => open,
+                    });
+                )*
+                base.unwrap_or_else(BitSet::new)
             }
 
-            fn get(&self, index: usize) -> Self::Item {
+            unsafe fn get(&mut self, index: usize) -> Self::Item {
                 #![allow(unused_variables, non_snake_case)]
 
                 let ( $($ty,)* ) = self;
                 ( $( $ty.get(index), )* )
             }
+
+            fn chunked(&mut self) -> Option<&mut ChunkedJoin<Item = Self::Item>> {
+                #![allow(unused_variables, non_snake_case)]
+
+                {
+                    let ( $($ty, )* ) = &mut *self;
+                    $( $ty.chunked()?; )*
+                }
+                Some(self)
+            }
+        }
+
+        impl<$($ty),*> ChunkedJoin for ( $( $ty , )* )
+            where $( $ty : Join ),*
+        {
+            type Item = ( $($ty::Item,)* );
+
+            fn chunks(&mut self) -> Option<Chunks> {
+                #![allow(unused_variables, non_snake_case)]
+
+                let mut base: Option<Chunks> = None;
+                let ( $($ty, )* ) = self;
+                $(
+                    let chunks = $ty.chunked()?.chunks()?;
+                    base = Some(match base {
+                        Some(base) => base.intersect(&chunks)?,
+                        None => chunks,
+                    });
+                )*
+                base
+            }
+
+            unsafe fn get_row(&mut self, archetype: ArchetypeId, row: usize) -> Self::Item {
+                #![allow(unused_variables, non_snake_case)]
+
+                let ( $($ty,)* ) = self;
+                ( $( $ty.chunked().expect("Join isn't laid out in chunks").get_row(archetype, row), )* )
+            }
         }
     };
 }
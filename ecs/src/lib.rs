@@ -12,20 +12,37 @@ extern crate bit_set;
 extern crate bit_vec;
 extern crate fxhash;
 
+pub mod command;
 pub mod component;
 pub mod entity;
+pub mod event;
+pub mod hierarchy;
+pub mod index;
 pub mod join;
+pub mod order;
 pub mod resource;
+pub mod snapshot;
 pub mod system;
 
-use component::storage::MaskedStorage;
+use bit_set::BitSet;
+use std::fmt::Debug;
+use std::ptr;
+
+use command::Commands;
+use component::bundle::Bundle;
+use component::registry::{ComponentInfo, ComponentRegistry, EntityDump};
+use component::storage::{Archetypes, MaskedStorage};
 use component::Component;
-use entity::EntityStorage;
-use resource::Resources;
-use system::{System, SystemData};
+use entity::{Entity, EntityMap, EntityStorage};
+use hierarchy::{Hierarchy, Parent};
+use index::{ComponentIndex, IndexKey};
+use order::SortOrder;
+use resource::{Fetch, FetchLocal, FetchLocalMut, FetchMut, LocalResource, Resource, Resources};
+use snapshot::{ResourceSnapshots, Snapshot};
+use system::{ErrorPolicy, FallibleSystem, System, SystemData, SystemError};
 
 trait SystemRunner<'a> {
-    fn run(&mut self, resources: &'a Resources);
+    fn run(&mut self, resources: &'a Resources) -> Result<(), SystemError>;
 }
 
 impl<'a, T, S> SystemRunner<'a> for S
@@ -33,44 +50,380 @@ where
     T: SystemData<'a>,
     S: System<'a, SystemData = T>,
 {
-    fn run(&mut self, resources: &'a Resources) {
-        self.run(<T>::fetch(resources))
+    fn run(&mut self, resources: &'a Resources) -> Result<(), SystemError> {
+        self.run(<T>::fetch(resources));
+        Ok(())
+    }
+}
+
+// Wrapper so fallible systems don't clash with the blanket impl above.
+struct FallibleRunner<S> {
+    name: String,
+    system: S,
+}
+
+impl<'a, T, S> SystemRunner<'a> for FallibleRunner<S>
+where
+    T: SystemData<'a>,
+    S: FallibleSystem<'a, SystemData = T>,
+{
+    fn run(&mut self, resources: &'a Resources) -> Result<(), SystemError> {
+        let name = &self.name;
+        self.system
+            .run(<T>::fetch(resources))
+            .map_err(|error| SystemError {
+                system: name.clone(),
+                error: Box::new(error),
+            })
     }
 }
 
 pub struct World {
     pub(crate) resources: Resources,
+    registry: ComponentRegistry,
+    resource_snapshots: ResourceSnapshots,
 }
 
 impl World {
     pub fn new() -> Self {
         let mut resources = Resources::new();
         resources.add(EntityStorage::new());
-        World { resources }
+        resources.add(Commands::default());
+        resources.add(Archetypes::default());
+        World {
+            resources,
+            registry: Default::default(),
+            resource_snapshots: Default::default(),
+        }
     }
 
     pub fn register<T>(&mut self) -> &mut Self
     where
         T: Component,
     {
-        self.resources.add(<MaskedStorage<T>>::new());
+        let commands = self.resources.fetch::<Commands>().clone();
+        let mut storage = <MaskedStorage<T>>::with_commands(commands);
+        storage.attach(&self.resources.fetch::<Archetypes>());
+        self.resources.add(storage);
+        self.registry.register::<T>();
+        self
+    }
+
+    // Components registered through here show their values in `dump`.
+    pub fn enable_debug<T>(&mut self) -> &mut Self
+    where
+        T: Component + Debug,
+    {
+        self.registry.enable_debug::<T>();
+        self
+    }
+
+    // Only components and resources enabled here are captured by `snapshot`.
+    pub fn enable_snapshot<T>(&mut self) -> &mut Self
+    where
+        T: Component + Clone,
+    {
+        self.registry.enable_snapshot::<T>();
+        self
+    }
+
+    pub fn enable_resource_snapshot<R>(&mut self) -> &mut Self
+    where
+        R: Resource + Clone,
+    {
+        self.resource_snapshots.enable::<R>();
+        self
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            entities: self.resources.fetch::<EntityStorage>().snapshot(),
+            components: self.registry.snapshot(&self.resources),
+            resources: self.resource_snapshots.snapshot(&self.resources),
+        }
+    }
+
+    // Puts the world back exactly as it was, so hooks don't run and commands
+    // queued since the snapshot are dropped. Trackers still see the restored
+    // components as inserted, modified or removed. Entities that didn't exist
+    // in the snapshot lose all their components, not just the ones with
+    // snapshots enabled.
+    pub fn restore(&self, snapshot: &Snapshot) {
+        self.resources.fetch::<Commands>().take();
+
+        let spawned: Vec<Entity> = {
+            let entities = self.resources.fetch::<EntityStorage>();
+            entities.alive().difference(snapshot.entities.alive()).collect()
+        };
+        for entity in spawned {
+            self.registry.forget_all(&self.resources, entity);
+        }
+
+        self.resources
+            .fetch::<EntityStorage>()
+            .restore(&snapshot.entities);
+        self.registry.restore(&self.resources, snapshot);
+        self.resource_snapshots.restore(&self.resources, snapshot);
+    }
+
+    pub fn components(&self) -> Vec<ComponentInfo> {
+        self.registry.components(&self.resources)
+    }
+
+    pub fn composition(&self, entity: Entity) -> Vec<ComponentInfo> {
+        self.registry.composition(&self.resources, entity)
+    }
+
+    pub fn dump(&self, entity: Entity) -> EntityDump {
+        self.registry.dump(&self.resources, entity)
+    }
+
+    pub fn create_entities(&self, count: usize) -> Vec<Entity> {
+        self.resources.fetch::<EntityStorage>().create_many(count)
+    }
+
+    // Applies queued commands, including the ones they queue themselves, then
+    // lets storages lay their components out again.
+    pub fn maintain(&self) {
+        loop {
+            let commands = self.resources.fetch::<Commands>().take();
+            if commands.is_empty() {
+                break;
+            }
+
+            for command in commands {
+                command.apply(self);
+            }
+        }
+
+        self.registry.maintain(&self.resources);
+    }
+
+    // Restores the iteration order of storages that reorder on removal,
+    // worth doing after lots of churn.
+    pub fn compact(&self) {
+        self.registry.compact(&self.resources);
+    }
+
+    // Removes all registered components of the entity and destroys it,
+    // along with its descendants if the hierarchy is registered.
+    pub fn destroy(&self, entity: Entity) {
+        let mut doomed = vec![entity];
+        doomed.extend(self.descendants(entity));
+
+        let entities = self.resources.fetch::<EntityStorage>();
+        for entity in doomed {
+            self.registry.remove_all(&self.resources, entity);
+            entities.destroy(entity);
+        }
+    }
+
+    // Empty if the hierarchy isn't registered.
+    fn descendants(&self, entity: Entity) -> Vec<Entity> {
+        match self.resources.try_fetch_mut::<Hierarchy>() {
+            Some(mut hierarchy) => {
+                let entities = self.resources.fetch::<EntityStorage>();
+                let parents = self.resources.fetch::<MaskedStorage<Parent>>();
+                let commands = self.resources.fetch::<Commands>();
+                hierarchy.maintain(&entities, &parents, &commands);
+                hierarchy.descendants(entity)
+            }
+            None => Vec::new(),
+        }
+    }
+
+    // Moves the entity and all of its components into `target`, returning
+    // its id there.
+    pub fn migrate(&self, entity: Entity, target: &World) -> Entity {
+        self.migrate_entities(&[entity], target)[&entity]
+    }
+
+    // Descendants come along if the hierarchy is registered and show up in
+    // the returned map too. `Parent` components are remapped to the new ids,
+    // or dropped if the parent stays behind. Other references have to be
+    // fixed up with the returned map. Components are moved, so hooks don't
+    // run on either side.
+    pub fn migrate_entities(&self, entities: &[Entity], target: &World) -> EntityMap {
+        if ptr::eq(self, target) {
+            panic!("Can't migrate entities into the same world!");
+        }
+
+        let mut seen = BitSet::new();
+        for entity in entities.iter() {
+            self.resources.fetch::<EntityStorage>().assert_alive(*entity);
+            if !seen.insert(*entity) {
+                panic!("Entity {} is migrated twice!", entity);
+            }
+        }
+
+        let mut migrated = entities.to_vec();
+        for entity in entities.iter() {
+            for descendant in self.descendants(*entity) {
+                if seen.insert(descendant) {
+                    migrated.push(descendant);
+                }
+            }
+        }
+        let entities = &migrated[..];
+
+        for entity in entities.iter() {
+            self.registry
+                .assert_migratable(&self.resources, *entity, &target.registry);
+        }
+
+        let created = target.create_entities(entities.len());
+        let map: EntityMap = entities.iter().cloned().zip(created.iter().cloned()).collect();
+
+        let storage = self.resources.fetch::<EntityStorage>();
+        for (entity, new) in entities.iter().zip(created.iter()) {
+            self.registry
+                .migrate(&self.resources, *entity, &target.resources, *new);
+            storage.destroy(*entity);
+        }
+
+        if target.registry.contains::<Parent>() {
+            let mut parents = target.resources.fetch_mut::<MaskedStorage<Parent>>();
+            for new in created {
+                let parent = match parents.get(new) {
+                    Some(parent) => parent.0,
+                    None => continue,
+                };
+
+                match map.get(&parent) {
+                    Some(parent) => parents.insert(new, Parent(*parent)),
+                    None => {
+                        parents.remove(new);
+                    }
+                }
+            }
+        }
+
+        map
+    }
+
+    // Lookups go through `ReadIndex<K, T>`. `maintain` syncs the index as
+    // well, so changes don't pile up while nothing looks it up.
+    pub fn register_index<K, T>(&mut self, key: fn(&T) -> K) -> &mut Self
+    where
+        K: IndexKey,
+        T: Component,
+    {
+        let index = ComponentIndex::new(&mut self.resources.fetch_mut::<MaskedStorage<T>>(), key);
+        self.resources.add(index);
+        self.registry.add_view::<T>(index::maintain::<K, T>);
+        self
+    }
+
+    // Ordered iteration goes through `ReadOrder<K, T>`, `maintain` keeps up
+    // with changes like for indices.
+    pub fn register_order<K, T>(&mut self, key: fn(&T) -> K) -> &mut Self
+    where
+        K: Ord + 'static,
+        T: Component,
+    {
+        let order = SortOrder::new(&mut self.resources.fetch_mut::<MaskedStorage<T>>(), key);
+        self.resources.add(order);
+        self.registry.add_view::<T>(order::maintain::<K, T>);
+        self
+    }
+
+    pub fn insert_bundle<B>(&self, entity: Entity, bundle: B)
+    where
+        B: Bundle,
+    {
+        self.resources.fetch::<EntityStorage>().assert_alive(entity);
+        bundle.insert(&self.resources, entity)
+    }
+
+    pub fn remove_bundle<B>(&self, entity: Entity) -> Option<B>
+    where
+        B: Bundle,
+    {
+        self.resources.fetch::<EntityStorage>().assert_alive(entity);
+        B::remove(&self.resources, entity)
+    }
+
+    pub fn register_hierarchy(&mut self) -> &mut Self {
+        self.register::<Parent>();
+        let hierarchy = Hierarchy::new(&mut self.resources.fetch_mut::<MaskedStorage<Parent>>());
+        self.resources.add(hierarchy);
+        self
+    }
+
+    pub fn add_local_resource<R>(&mut self, resource: R) -> &mut Self
+    where
+        R: LocalResource,
+    {
+        self.resources.add_local(resource);
+        self
+    }
+
+    pub fn fetch_local<R>(&self) -> FetchLocal<R>
+    where
+        R: LocalResource,
+    {
+        self.resources.fetch_local::<R>()
+    }
+
+    pub fn fetch_local_mut<R>(&self) -> FetchLocalMut<R>
+    where
+        R: LocalResource,
+    {
+        self.resources.fetch_local_mut::<R>()
+    }
+
+    // Runs `f` once with the requested system data, without a dispatcher.
+    pub fn exec<'a, T, F, R>(&'a self, f: F) -> R
+    where
+        T: SystemData<'a>,
+        F: FnOnce(T) -> R,
+    {
+        f(<T>::fetch(&self.resources))
+    }
+
+    pub fn add_resource<R>(&mut self, resource: R) -> &mut Self
+    where
+        R: Resource,
+    {
+        self.resources.add(resource);
         self
     }
+
+    pub fn fetch<R>(&self) -> Fetch<R>
+    where
+        R: Resource,
+    {
+        self.resources.fetch::<R>()
+    }
+
+    pub fn fetch_mut<R>(&self) -> FetchMut<R>
+    where
+        R: Resource,
+    {
+        self.resources.fetch_mut::<R>()
+    }
 }
 
 // The only reason we have this type is because
 // we can't do a self borrow.
 pub struct Dispatcher<'a> {
     systems: Vec<Box<SystemRunner<'a> + 'a>>,
+    policy: ErrorPolicy,
 }
 
 impl<'a> Dispatcher<'a> {
     pub fn new() -> Self {
         Dispatcher {
             systems: Vec::new(),
+            policy: Default::default(),
         }
     }
 
+    pub fn set_error_policy(&mut self, policy: ErrorPolicy) -> &mut Self {
+        self.policy = policy;
+        self
+    }
+
     pub fn register<T>(&mut self, system: T) -> &mut Self
     where
         T: 'a + System<'a>,
@@ -79,10 +432,33 @@ impl<'a> Dispatcher<'a> {
         self
     }
 
-    pub fn dispatch(&mut self, world: &'a World) {
+    pub fn register_fallible<T>(&mut self, name: &str, system: T) -> &mut Self
+    where
+        T: 'a + FallibleSystem<'a>,
+    {
+        self.systems.push(Box::new(FallibleRunner {
+            name: name.to_owned(),
+            system,
+        }));
+        self
+    }
+
+    pub fn dispatch(&mut self, world: &'a World) -> Result<(), Vec<SystemError>> {
+        let mut errors = Vec::new();
         let systems = self.systems.iter_mut();
         for system in systems {
-            system.run(&world.resources);
+            if let Err(error) = system.run(&world.resources) {
+                errors.push(error);
+                if self.policy == ErrorPolicy::Abort {
+                    break;
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
         }
     }
 }
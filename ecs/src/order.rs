@@ -0,0 +1,209 @@
+use bit_set::BitSet;
+use std::ops::Deref;
+use std::slice::Iter;
+
+use super::component::storage::MaskedStorage;
+use super::component::Component;
+use super::entity::Entity;
+use super::event::ReaderId;
+use super::join::Join;
+use super::resource::{Fetch, FetchMut, Resources};
+use super::system::SystemData;
+
+// Entities holding `C`, sorted by a key derived from it. The order is cached
+// and only rebuilt when the storage reports changes.
+pub struct SortOrder<K: Ord, C: Component> {
+    key: fn(&C) -> K,
+    order: Vec<Entity>,
+    dirty: bool,
+    reader: ReaderId,
+}
+
+impl<K, C> SortOrder<K, C>
+where
+    K: Ord,
+    C: Component,
+{
+    pub fn new(storage: &mut MaskedStorage<C>, key: fn(&C) -> K) -> Self {
+        SortOrder {
+            key,
+            order: Vec::new(),
+            dirty: true,
+            reader: storage.track(),
+        }
+    }
+
+    pub fn entities(&self) -> &[Entity] {
+        &self.order
+    }
+
+    pub fn maintain(&mut self, storage: &MaskedStorage<C>) {
+        if !storage.events(&mut self.reader).is_empty() {
+            self.dirty = true;
+        }
+
+        if !self.dirty {
+            return;
+        }
+
+        let key = self.key;
+        let component = |entity: &Entity| storage.get(*entity).expect("Component not found!");
+        self.order.clear();
+        self.order.extend(storage.mask().iter());
+        // ties are broken by entity so the order is stable across rebuilds
+        self.order.sort_unstable_by(|a, b| {
+            key(component(a))
+                .cmp(&key(component(b)))
+                .then(a.cmp(b))
+        });
+        self.dirty = false;
+    }
+
+    // Iterates `join` following the cached order, skipping entities it doesn't match.
+    pub fn join<J: Join>(&self, join: J) -> SortedJoinIter<J> {
+        SortedJoinIter {
+            mask: join.open(),
+            entities: self.order.iter(),
+            join,
+        }
+    }
+}
+
+pub struct SortedJoinIter<'a, J: Join> {
+    mask: BitSet,
+    entities: Iter<'a, Entity>,
+    join: J,
+}
+
+impl<'a, J> Iterator for SortedJoinIter<'a, J>
+where
+    J: Join,
+{
+    type Item = J::Item;
+
+    fn next(&mut self) -> Option<J::Item> {
+        while let Some(entity) = self.entities.next() {
+            if self.mask.contains(*entity) {
+                // the order holds every entity once
+                return Some(unsafe { self.join.get(*entity) });
+            }
+        }
+        None
+    }
+}
+
+// Brought up to date when fetched. Like `ReadIndex` it keeps the storage
+// borrowed, so it can't be fetched along with a `WriteStorage<C>`.
+pub struct ReadOrder<'a, K: 'a + Ord, C: 'a + Component> {
+    order: FetchMut<'a, SortOrder<K, C>>,
+    _storage: Fetch<'a, MaskedStorage<C>>,
+}
+
+impl<'a, K, C> Deref for ReadOrder<'a, K, C>
+where
+    K: Ord + 'static,
+    C: Component,
+{
+    type Target = SortOrder<K, C>;
+
+    fn deref(&self) -> &SortOrder<K, C> {
+        &self.order
+    }
+}
+
+impl<'a, K, C> SystemData<'a> for ReadOrder<'a, K, C>
+where
+    K: Ord + 'static,
+    C: Component,
+{
+    fn fetch(res: &'a Resources) -> Self {
+        let mut order = res.fetch_mut::<SortOrder<K, C>>();
+        let storage = res.fetch::<MaskedStorage<C>>();
+        order.maintain(&storage);
+        ReadOrder {
+            order,
+            _storage: storage,
+        }
+    }
+}
+
+pub(crate) fn maintain<K, C>(resources: &Resources)
+where
+    K: Ord + 'static,
+    C: Component,
+{
+    resources
+        .fetch_mut::<SortOrder<K, C>>()
+        .maintain(&resources.fetch::<MaskedStorage<C>>());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::component::storage::VecStorage;
+    use super::*;
+
+    struct ZOrder(i32);
+    impl Component for ZOrder {
+        type Storage = VecStorage<Self>;
+    }
+
+    fn z(component: &ZOrder) -> i32 {
+        component.0
+    }
+
+    #[test]
+    fn sorted() {
+        let mut storage = <MaskedStorage<ZOrder>>::new();
+        let mut order = SortOrder::new(&mut storage, z);
+        storage.insert(0, ZOrder(3));
+        storage.insert(1, ZOrder(1));
+        storage.insert(2, ZOrder(2));
+        storage.insert(3, ZOrder(1));
+        order.maintain(&storage);
+
+        assert_eq!(order.entities(), &[1, 3, 2, 0]);
+    }
+
+    #[test]
+    fn invalidated_by_changes() {
+        let mut storage = <MaskedStorage<ZOrder>>::new();
+        let mut order = SortOrder::new(&mut storage, z);
+        storage.insert(0, ZOrder(1));
+        storage.insert(1, ZOrder(2));
+        order.maintain(&storage);
+
+        storage.get_mut(0).unwrap().0 = 5;
+        order.maintain(&storage);
+        assert_eq!(order.entities(), &[1, 0]);
+
+        storage.remove(1);
+        order.maintain(&storage);
+        assert_eq!(order.entities(), &[0]);
+    }
+
+    #[test]
+    fn reuses_order() {
+        let mut storage = <MaskedStorage<ZOrder>>::new();
+        let mut order = SortOrder::new(&mut storage, z);
+        storage.insert(0, ZOrder(1));
+        order.maintain(&storage);
+        let before = order.entities().as_ptr();
+
+        order.maintain(&storage);
+        assert!(!order.dirty);
+        assert_eq!(before, order.entities().as_ptr());
+    }
+
+    #[test]
+    fn join() {
+        let mut storage = <MaskedStorage<ZOrder>>::new();
+        let mut order = SortOrder::new(&mut storage, z);
+        storage.insert(0, ZOrder(2));
+        storage.insert(1, ZOrder(1));
+        storage.insert(2, ZOrder(0));
+        order.maintain(&storage);
+
+        let values: Vec<i32> = order.join((&storage,)).map(|(c,)| c.0).collect();
+        assert_eq!(values, vec![0, 1, 2]);
+    }
+}
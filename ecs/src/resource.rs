@@ -19,6 +19,19 @@ where
 {
 }
 
+// Resources that can't leave the thread that created the world, e.g. a
+// Lua state or an Rc based cache. Holding them makes `Resources` !Send, so
+// the world can't leave that thread either.
+pub trait LocalResource: Any {}
+
+mopafy!(LocalResource);
+
+impl<T> LocalResource for T
+where
+    T: Any,
+{
+}
+
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct ResourceId(pub TypeId);
 
@@ -69,9 +82,51 @@ where
     }
 }
 
+pub struct FetchLocal<'a, T: 'a> {
+    inner: Ref<'a, Box<LocalResource>>,
+    phantom: PhantomData<&'a T>,
+}
+
+impl<'a, T> Deref for FetchLocal<'a, T>
+where
+    T: LocalResource,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.inner.downcast_ref_unchecked() }
+    }
+}
+
+pub struct FetchLocalMut<'a, T: 'a> {
+    inner: RefMut<'a, Box<LocalResource>>,
+    phantom: PhantomData<&'a mut T>,
+}
+
+impl<'a, T> Deref for FetchLocalMut<'a, T>
+where
+    T: LocalResource,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.inner.downcast_ref_unchecked() }
+    }
+}
+
+impl<'a, T> DerefMut for FetchLocalMut<'a, T>
+where
+    T: LocalResource,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.inner.downcast_mut_unchecked() }
+    }
+}
+
 #[derive(Default)]
 pub struct Resources {
     resources: FxHashMap<ResourceId, RefCell<Box<Resource>>>,
+    locals: FxHashMap<ResourceId, RefCell<Box<LocalResource>>>,
 }
 
 impl Resources {
@@ -90,7 +145,7 @@ impl Resources {
         if let Entry::Vacant(e) = entry {
             e.insert(RefCell::new(Box::new(resource)));
         } else {
-            panic!("Resouce already exists!");
+            panic!("Resource already exists!");
         }
     }
 
@@ -136,11 +191,65 @@ impl Resources {
     fn try_fetch_internal(&self, id: TypeId) -> Option<&RefCell<Box<Resource>>> {
         self.resources.get(&ResourceId(id))
     }
+
+    pub fn add_local<R>(&mut self, resource: R)
+    where
+        R: LocalResource,
+    {
+        use std::collections::hash_map::Entry;
+
+        let entry = self.locals.entry(ResourceId(TypeId::of::<R>()));
+
+        if let Entry::Vacant(e) = entry {
+            e.insert(RefCell::new(Box::new(resource)));
+        } else {
+            panic!("Resource already exists!");
+        }
+    }
+
+    pub fn has_local(&self, res_id: ResourceId) -> bool {
+        self.locals.contains_key(&res_id)
+    }
+
+    pub fn fetch_local<T>(&self) -> FetchLocal<T>
+    where
+        T: LocalResource,
+    {
+        self.try_fetch_local().expect(RESOURCE_NOT_FOUND)
+    }
+
+    pub fn try_fetch_local<T>(&self) -> Option<FetchLocal<T>>
+    where
+        T: LocalResource,
+    {
+        self.locals.get(&ResourceId(TypeId::of::<T>())).map(|r| FetchLocal {
+            inner: r.borrow(),
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn fetch_local_mut<T>(&self) -> FetchLocalMut<T>
+    where
+        T: LocalResource,
+    {
+        self.try_fetch_local_mut().expect(RESOURCE_NOT_FOUND)
+    }
+
+    pub fn try_fetch_local_mut<T>(&self) -> Option<FetchLocalMut<T>>
+    where
+        T: LocalResource,
+    {
+        self.locals.get(&ResourceId(TypeId::of::<T>())).map(|r| FetchLocalMut {
+            inner: r.borrow_mut(),
+            phantom: PhantomData,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::rc::Rc;
 
     struct Res;
     impl Res {
@@ -203,4 +312,26 @@ mod tests {
             assert_eq!(*res.fetch::<i32>(), 10);
         }
     }
+
+    #[test]
+    fn local() {
+        let mut res = Resources::new();
+        res.add_local(Rc::new(5i32));
+
+        assert!(res.has_local(ResourceId(TypeId::of::<Rc<i32>>())));
+        assert!(!res.has_value(ResourceId(TypeId::of::<Rc<i32>>())));
+        assert_eq!(**res.fetch_local::<Rc<i32>>(), 5);
+    }
+
+    #[test]
+    fn local_mutate() {
+        let mut res = Resources::new();
+        res.add_local(Rc::new(5i32));
+
+        {
+            *res.fetch_local_mut::<Rc<i32>>() = Rc::new(10);
+        }
+
+        assert_eq!(**res.fetch_local::<Rc<i32>>(), 10);
+    }
 }
@@ -0,0 +1,109 @@
+use bit_set::BitSet;
+use std::any::{Any, TypeId};
+
+use super::component::storage::MaskedStorage;
+use super::component::Component;
+use super::entity::EntitySnapshot;
+use super::resource::{Resource, Resources};
+
+pub(crate) type SnapshotFn = fn(&Resources) -> Box<Any>;
+pub(crate) type RestoreFn = fn(&Resources, &Any);
+
+// In-memory copy of the world taken by `World::snapshot`. Only components
+// and resources that have snapshots enabled are captured.
+pub struct Snapshot {
+    pub(crate) entities: EntitySnapshot,
+    pub(crate) components: Vec<(TypeId, Box<Any>)>,
+    pub(crate) resources: Vec<(TypeId, Box<Any>)>,
+}
+
+impl Snapshot {
+    pub(crate) fn component(&self, id: TypeId) -> Option<&Any> {
+        find(&self.components, id)
+    }
+
+    pub(crate) fn resource(&self, id: TypeId) -> Option<&Any> {
+        find(&self.resources, id)
+    }
+}
+
+fn find(data: &[(TypeId, Box<Any>)], id: TypeId) -> Option<&Any> {
+    data.iter()
+        .find(|&&(data_id, _)| data_id == id)
+        .map(|&(_, ref data)| data.as_ref())
+}
+
+pub(crate) fn snapshot_component<T: Component + Clone>(resources: &Resources) -> Box<Any> {
+    let storage = resources.fetch::<MaskedStorage<T>>();
+    let components: Vec<(usize, T)> = storage
+        .mask()
+        .iter()
+        .map(|index| (index, storage.get(index).expect("Component not found!").clone()))
+        .collect();
+    Box::new(components)
+}
+
+pub(crate) fn restore_component<T: Component + Clone>(resources: &Resources, data: &Any) {
+    let components = data.downcast_ref::<Vec<(usize, T)>>()
+        .expect("Snapshot type mismatch!");
+    let mut storage = resources.fetch_mut::<MaskedStorage<T>>();
+    let restored: BitSet = components.iter().map(|&(index, _)| index).collect();
+    let stale: Vec<usize> = storage.mask().difference(&restored).collect();
+    for index in stale {
+        storage.forget(index);
+    }
+
+    for &(index, ref component) in components.iter() {
+        storage.put(index, component.clone());
+    }
+}
+
+pub(crate) fn snapshot_resource<R: Resource + Clone>(resources: &Resources) -> Box<Any> {
+    Box::new(resources.fetch::<R>().clone())
+}
+
+pub(crate) fn restore_resource<R: Resource + Clone>(resources: &Resources, data: &Any) {
+    let resource = data.downcast_ref::<R>().expect("Snapshot type mismatch!");
+    *resources.fetch_mut::<R>() = resource.clone();
+}
+
+struct ResourceEntry {
+    id: TypeId,
+    snapshot: SnapshotFn,
+    restore: RestoreFn,
+}
+
+#[derive(Default)]
+pub(crate) struct ResourceSnapshots {
+    entries: Vec<ResourceEntry>,
+}
+
+impl ResourceSnapshots {
+    pub fn enable<R: Resource + Clone>(&mut self) {
+        let id = TypeId::of::<R>();
+        if self.entries.iter().any(|entry| entry.id == id) {
+            return;
+        }
+
+        self.entries.push(ResourceEntry {
+            id,
+            snapshot: snapshot_resource::<R>,
+            restore: restore_resource::<R>,
+        });
+    }
+
+    pub fn snapshot(&self, resources: &Resources) -> Vec<(TypeId, Box<Any>)> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.id, (entry.snapshot)(resources)))
+            .collect()
+    }
+
+    pub fn restore(&self, resources: &Resources, snapshot: &Snapshot) {
+        for entry in self.entries.iter() {
+            if let Some(data) = snapshot.resource(entry.id) {
+                (entry.restore)(resources, data);
+            }
+        }
+    }
+}
@@ -1,6 +1,9 @@
+use std::error::Error;
+use std::fmt;
 use std::marker::PhantomData;
 
-use super::resource::{Fetch, FetchMut, Resource, Resources};
+use super::resource::{Fetch, FetchLocal, FetchLocalMut, FetchMut, LocalResource, Resource,
+                      Resources};
 
 pub trait System<'a> {
     type SystemData: SystemData<'a>;
@@ -8,6 +11,40 @@ pub trait System<'a> {
     fn run(&mut self, data: Self::SystemData);
 }
 
+pub trait FallibleSystem<'a> {
+    type SystemData: SystemData<'a>;
+    type Error: Error + 'static;
+
+    fn run(&mut self, data: Self::SystemData) -> Result<(), Self::Error>;
+}
+
+#[derive(Debug)]
+pub struct SystemError {
+    pub system: String,
+    pub error: Box<Error>,
+}
+
+impl fmt::Display for SystemError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "System {} failed: {}", self.system, self.error)
+    }
+}
+
+impl Error for SystemError {}
+
+// What the dispatcher does with the rest of the frame once a system fails.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ErrorPolicy {
+    Abort,
+    Continue,
+}
+
+impl Default for ErrorPolicy {
+    fn default() -> Self {
+        ErrorPolicy::Abort
+    }
+}
+
 pub trait SystemData<'a> {
     fn fetch(res: &'a Resources) -> Self;
 }
@@ -36,6 +73,24 @@ where
     }
 }
 
+impl<'a, R> SystemData<'a> for FetchLocal<'a, R>
+where
+    R: LocalResource,
+{
+    fn fetch(res: &'a Resources) -> Self {
+        res.fetch_local::<R>()
+    }
+}
+
+impl<'a, R> SystemData<'a> for FetchLocalMut<'a, R>
+where
+    R: LocalResource,
+{
+    fn fetch(res: &'a Resources) -> Self {
+        res.fetch_local_mut::<R>()
+    }
+}
+
 macro_rules! impl_data {
     ( $($ty:ident),* ) => {
         impl<'a, $($ty),*> SystemData<'a> for ( $( $ty , )* )
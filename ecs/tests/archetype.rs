@@ -0,0 +1,124 @@
+extern crate ecs;
+#[macro_use]
+extern crate ecs_derive;
+
+use ecs::component::storage::{ArchetypeStorage, Archetypes, ComponentEvent, MaskedStorage};
+use ecs::component::{Component, ReadStorage, WriteStorage};
+use ecs::entity::Entities;
+use ecs::join::Join;
+use ecs::World;
+
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+#[Storage(ArchetypeStorage)]
+struct Position(usize);
+
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+#[Storage(ArchetypeStorage)]
+struct Velocity(usize);
+
+fn world() -> World {
+    let mut world = World::new();
+    world.register::<Position>();
+    world.register::<Velocity>();
+
+    let entities = world.create_entities(6);
+    world.exec(|(mut positions, mut velocities): (WriteStorage<Position>, WriteStorage<Velocity>)| {
+        for entity in entities {
+            positions.insert(entity, Position(entity));
+            if entity % 2 == 1 {
+                velocities.insert(entity, Velocity(entity));
+            }
+        }
+    });
+    world
+}
+
+fn moving(world: &World) -> Vec<usize> {
+    world.exec(|(positions, velocities): (ReadStorage<Position>, ReadStorage<Velocity>)| {
+        let mut moving: Vec<usize> = (&positions, &velocities)
+            .join()
+            .map(|(position, velocity)| {
+                assert_eq!(position.0, velocity.0);
+                position.0
+            })
+            .collect();
+        moving.sort();
+        moving
+    })
+}
+
+#[test]
+fn grouped_by_archetype() {
+    let world = world();
+    world.maintain();
+
+    let archetypes = world.fetch::<Archetypes>();
+    assert_eq!(archetypes.archetype(1), archetypes.archetype(3));
+    assert_ne!(archetypes.archetype(0), archetypes.archetype(1));
+    assert_eq!(moving(&world), vec![1, 3, 5]);
+}
+
+#[test]
+fn join_before_maintain() {
+    let world = world();
+    world.exec(|(mut positions, mut velocities): (WriteStorage<Position>, WriteStorage<Velocity>)| {
+        velocities.insert(0, Velocity(0));
+        velocities.remove(3);
+        positions.remove(5);
+    });
+    assert_eq!(moving(&world), vec![0, 1]);
+
+    world.maintain();
+    assert_eq!(moving(&world), vec![0, 1]);
+}
+
+#[test]
+fn join_with_entities() {
+    let world = world();
+    world.maintain();
+
+    world.exec(|(entities, velocities): (Entities, ReadStorage<Velocity>)| {
+        let joined: Vec<(usize, usize)> = (&entities, &velocities)
+            .join()
+            .map(|(entity, velocity)| (entity, velocity.0))
+            .collect();
+        assert_eq!(joined, vec![(1, 1), (3, 3), (5, 5)]);
+    });
+}
+
+#[test]
+fn mutable_join_tracks_changes() {
+    let world = world();
+    world.maintain();
+    let mut reader = world.fetch_mut::<MaskedStorage<Position>>().track();
+
+    world.exec(|(mut positions, velocities): (WriteStorage<Position>, ReadStorage<Velocity>)| {
+        for (position, velocity) in (&mut positions, &velocities).join() {
+            position.0 += velocity.0;
+        }
+    });
+
+    let positions = world.fetch::<MaskedStorage<Position>>();
+    assert_eq!(positions.get(3), Some(&Position(6)));
+    assert_eq!(positions.get(2), Some(&Position(2)));
+    let mut modified: Vec<usize> = positions
+        .events(&mut reader)
+        .iter()
+        .map(|event| match *event {
+            ComponentEvent::Modified(entity) => entity,
+            _ => panic!("Unexpected event!"),
+        })
+        .collect();
+    modified.sort();
+    assert_eq!(modified, vec![1, 3, 5]);
+}
+
+#[test]
+fn storages_of_different_worlds() {
+    let mut positions = <MaskedStorage<Position>>::new();
+    let mut velocities = <MaskedStorage<Velocity>>::new();
+    positions.insert(0, Position(0));
+    velocities.insert(1, Velocity(1));
+
+    assert_eq!((&positions, &velocities).join().count(), 0);
+}
@@ -0,0 +1,70 @@
+extern crate ecs;
+#[macro_use]
+extern crate ecs_derive;
+
+use ecs::component::storage::{MaskedStorage, VecStorage};
+use ecs::component::{Component, WriteStorage};
+use ecs::entity::{Entities, EntityStorage};
+use ecs::system::System;
+use ecs::{Dispatcher, World};
+
+#[derive(Component)]
+#[Storage(VecStorage)]
+struct Particle(usize);
+
+struct SpawnSystem;
+
+impl<'a> System<'a> for SpawnSystem {
+    type SystemData = (Entities<'a>, WriteStorage<'a, Particle>);
+
+    fn run(&mut self, (entities, mut particles): Self::SystemData) {
+        particles.extend(
+            entities
+                .create_iter()
+                .take(10)
+                .map(|entity| (entity, Particle(entity))),
+        );
+    }
+}
+
+#[test]
+fn create_entities() {
+    let world = World::new();
+    let entities = world.create_entities(10_000);
+    assert_eq!(entities.len(), 10_000);
+
+    let storage = world.fetch::<EntityStorage>();
+    assert!(entities.iter().all(|entity| storage.is_alive(*entity)));
+}
+
+#[test]
+fn extend_storage() {
+    let mut world = World::new();
+    world.register::<Particle>();
+    world.create_entities(5);
+
+    let mut dispatcher = Dispatcher::new();
+    dispatcher.register(SpawnSystem);
+    dispatcher.dispatch(&world).unwrap();
+
+    let particles = world.fetch::<MaskedStorage<Particle>>();
+    assert!(!particles.contains(0));
+    for entity in 5..15 {
+        assert_eq!(particles.get(entity).unwrap().0, entity);
+    }
+}
+
+#[test]
+fn create_entities_reuses_dead() {
+    let world = World::new();
+    let entities = world.create_entities(4);
+    {
+        let storage = world.fetch::<EntityStorage>();
+        storage.destroy(entities[1]);
+        storage.destroy(entities[3]);
+    }
+
+    let mut created = world.create_entities(3);
+    created.sort();
+    assert_eq!(created, vec![1, 3, 4]);
+}
@@ -0,0 +1,49 @@
+extern crate ecs;
+#[macro_use]
+extern crate ecs_derive;
+
+use ecs::component::storage::{MaskedStorage, VecStorage};
+use ecs::component::Component;
+use ecs::World;
+
+#[derive(Component, Debug, PartialEq)]
+#[Storage(VecStorage)]
+struct Position(i32);
+
+#[derive(Component, Debug, PartialEq)]
+#[Storage(VecStorage)]
+struct Velocity(i32);
+
+#[derive(Component, Debug, PartialEq)]
+#[Storage(VecStorage)]
+struct Sprite(&'static str);
+
+fn world() -> World {
+    let mut world = World::new();
+    world.register::<Position>();
+    world.register::<Velocity>();
+    world.register::<Sprite>();
+    world
+}
+
+#[test]
+fn insert_and_remove() {
+    let world = world();
+    let entity = world.create_entities(1)[0];
+    world.insert_bundle(entity, (Position(0), Velocity(1), Sprite("ship")));
+
+    let removed = world.remove_bundle::<(Position, Velocity)>(entity);
+    assert_eq!(removed, Some((Position(0), Velocity(1))));
+    assert!(!world.fetch::<MaskedStorage<Position>>().contains(entity));
+    assert_eq!(
+        world.fetch::<MaskedStorage<Sprite>>().get(entity),
+        Some(&Sprite("ship"))
+    );
+}
+
+#[test]
+#[should_panic]
+fn insert_dead_entity() {
+    let world = world();
+    world.insert_bundle(0, (Position(0),));
+}
@@ -0,0 +1,34 @@
+extern crate ecs;
+#[macro_use]
+extern crate ecs_derive;
+
+use ecs::component::storage::PackedStorage;
+use ecs::component::{Component, ReadStorage, WriteStorage};
+use ecs::join::Join;
+use ecs::World;
+
+#[derive(Component, Debug, PartialEq)]
+#[Storage(PackedStorage)]
+struct Health(u32);
+
+#[test]
+fn packed_storage() {
+    let mut world = World::new();
+    world.register::<Health>();
+    let entities = world.create_entities(4);
+
+    world.exec(|mut health: WriteStorage<Health>| {
+        for entity in entities.iter() {
+            health.insert(*entity, Health(*entity as u32));
+        }
+        health.remove(entities[1]);
+        health.get_mut(entities[3]).unwrap().0 = 30;
+    });
+    world.compact();
+
+    world.exec(|health: ReadStorage<Health>| {
+        let values: Vec<u32> = (&health,).join().map(|(h,)| h.0).collect();
+        assert_eq!(values, vec![0, 2, 30]);
+        assert_eq!(health.get(entities[1]), None);
+    });
+}
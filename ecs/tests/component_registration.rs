@@ -28,7 +28,7 @@ fn unregistered_component() {
     let mut world = World::new();
     let mut dispatcher = Dispatcher::new();
     dispatcher.register(MySystem);
-    dispatcher.dispatch(&world);
+    dispatcher.dispatch(&world).unwrap();
 }
 
 #[test]
@@ -37,5 +37,5 @@ fn registered_component() {
     world.register::<MyComponent>();
     let mut dispatcher = Dispatcher::new();
     dispatcher.register(MySystem);
-    dispatcher.dispatch(&world);
+    dispatcher.dispatch(&world).unwrap();
 }
@@ -0,0 +1,46 @@
+extern crate ecs;
+#[macro_use]
+extern crate ecs_derive;
+
+use ecs::component::storage::VecStorage;
+use ecs::component::{Component, ReadStorage, WriteStorage};
+use ecs::entity::Entities;
+use ecs::resource::Fetch;
+use ecs::World;
+
+#[derive(Component, Debug, PartialEq)]
+#[Storage(VecStorage)]
+struct Position(i32);
+
+struct Gravity(i32);
+
+#[test]
+fn exec() {
+    let mut world = World::new();
+    world.register::<Position>();
+    world.add_resource(Gravity(-1));
+
+    let entity = world.exec(
+        |(entities, mut positions): (Entities, WriteStorage<Position>)| {
+            let entity = entities.create();
+            positions.insert(entity, Position(10));
+            entity
+        },
+    );
+
+    world.exec(
+        |(gravity, mut positions): (Fetch<Gravity>, WriteStorage<Position>)| {
+            positions.get_mut(entity).unwrap().0 += gravity.0;
+        },
+    );
+
+    let position = world.exec(|positions: ReadStorage<Position>| positions.get(entity).map(|p| p.0));
+    assert_eq!(position, Some(9));
+}
+
+#[test]
+#[should_panic]
+fn exec_unregistered() {
+    let world = World::new();
+    world.exec(|_: ReadStorage<Position>| ());
+}
@@ -0,0 +1,100 @@
+extern crate ecs;
+
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+
+use ecs::entity::Entities;
+use ecs::system::{ErrorPolicy, FallibleSystem, System};
+use ecs::{Dispatcher, World};
+
+#[derive(Debug)]
+struct MyError;
+
+impl fmt::Display for MyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "my error")
+    }
+}
+
+impl Error for MyError {}
+
+// Counts the systems that ran.
+type Counter = Rc<Cell<i32>>;
+
+struct FailingSystem(Counter);
+
+impl<'a> FallibleSystem<'a> for FailingSystem {
+    type SystemData = Entities<'a>;
+    type Error = MyError;
+
+    fn run(&mut self, _: Self::SystemData) -> Result<(), MyError> {
+        self.0.set(self.0.get() + 1);
+        Err(MyError)
+    }
+}
+
+struct OkSystem(Counter);
+
+impl<'a> FallibleSystem<'a> for OkSystem {
+    type SystemData = Entities<'a>;
+    type Error = MyError;
+
+    fn run(&mut self, _: Self::SystemData) -> Result<(), MyError> {
+        self.0.set(self.0.get() + 1);
+        Ok(())
+    }
+}
+
+struct CountingSystem(Counter);
+
+impl<'a> System<'a> for CountingSystem {
+    type SystemData = Entities<'a>;
+
+    fn run(&mut self, _: Self::SystemData) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+#[test]
+fn no_errors() {
+    let world = World::new();
+    let counter = Counter::default();
+    let mut dispatcher = Dispatcher::new();
+    dispatcher.register_fallible("ok", OkSystem(counter.clone()));
+    dispatcher.register(CountingSystem(counter.clone()));
+    assert!(dispatcher.dispatch(&world).is_ok());
+    assert_eq!(counter.get(), 2);
+}
+
+#[test]
+fn abort_on_error() {
+    let world = World::new();
+    let counter = Counter::default();
+    let mut dispatcher = Dispatcher::new();
+    dispatcher.register_fallible("failing", FailingSystem(counter.clone()));
+    dispatcher.register(CountingSystem(counter.clone()));
+
+    let errors = dispatcher.dispatch(&world).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].system, "failing");
+    // the rest of the frame was skipped
+    assert_eq!(counter.get(), 1);
+}
+
+#[test]
+fn continue_on_error() {
+    let world = World::new();
+    let counter = Counter::default();
+    let mut dispatcher = Dispatcher::new();
+    dispatcher.set_error_policy(ErrorPolicy::Continue);
+    dispatcher.register_fallible("first", FailingSystem(counter.clone()));
+    dispatcher.register(CountingSystem(counter.clone()));
+    dispatcher.register_fallible("second", FailingSystem(counter.clone()));
+
+    let errors = dispatcher.dispatch(&world).unwrap_err();
+    let names: Vec<&str> = errors.iter().map(|e| e.system.as_str()).collect();
+    assert_eq!(names, vec!["first", "second"]);
+    assert_eq!(counter.get(), 3);
+}
@@ -0,0 +1,122 @@
+extern crate ecs;
+#[macro_use]
+extern crate ecs_derive;
+
+use ecs::component::storage::{MaskedStorage, VecStorage};
+use ecs::component::Component;
+use ecs::entity::EntityStorage;
+use ecs::hierarchy::{Hierarchy, HierarchySystem, Parent};
+use ecs::{Dispatcher, World};
+
+#[derive(Component)]
+#[Storage(VecStorage)]
+struct Weapon;
+
+#[test]
+fn maintained_by_system() {
+    let mut world = World::new();
+    world.register_hierarchy();
+    let entities = world.create_entities(2);
+    world
+        .fetch_mut::<MaskedStorage<Parent>>()
+        .insert(entities[1], Parent(entities[0]));
+
+    let mut dispatcher = Dispatcher::new();
+    dispatcher.register(HierarchySystem);
+    dispatcher.dispatch(&world).unwrap();
+
+    assert_eq!(world.fetch::<Hierarchy>().children(entities[0]), &[entities[1]]);
+}
+
+#[test]
+fn destroy_cascades() {
+    let mut world = World::new();
+    world.register_hierarchy();
+    world.register::<Weapon>();
+    let entities = world.create_entities(4);
+    {
+        let mut parents = world.fetch_mut::<MaskedStorage<Parent>>();
+        parents.insert(entities[1], Parent(entities[0]));
+        parents.insert(entities[2], Parent(entities[1]));
+        world
+            .fetch_mut::<MaskedStorage<Weapon>>()
+            .insert(entities[2], Weapon);
+    }
+
+    world.destroy(entities[0]);
+
+    let storage = world.fetch::<EntityStorage>();
+    assert!(!storage.is_alive(entities[0]));
+    assert!(!storage.is_alive(entities[1]));
+    assert!(!storage.is_alive(entities[2]));
+    assert!(storage.is_alive(entities[3]));
+    // components don't outlive their entities
+    assert!(!world.fetch::<MaskedStorage<Weapon>>().contains(entities[2]));
+    assert!(!world.fetch::<MaskedStorage<Parent>>().contains(entities[1]));
+}
+
+#[test]
+fn destroy_without_hierarchy() {
+    let mut world = World::new();
+    world.register::<Weapon>();
+    let entity = world.create_entities(1)[0];
+    world.fetch_mut::<MaskedStorage<Weapon>>().insert(entity, Weapon);
+
+    world.destroy(entity);
+
+    assert!(!world.fetch::<EntityStorage>().is_alive(entity));
+    assert!(!world.fetch::<MaskedStorage<Weapon>>().contains(entity));
+}
+
+#[test]
+fn orphans_destroyed_on_maintain() {
+    let mut world = World::new();
+    world.register_hierarchy();
+    world.register::<Weapon>();
+    let entities = world.create_entities(3);
+    {
+        let mut parents = world.fetch_mut::<MaskedStorage<Parent>>();
+        parents.insert(entities[1], Parent(entities[0]));
+        parents.insert(entities[2], Parent(entities[1]));
+        world
+            .fetch_mut::<MaskedStorage<Weapon>>()
+            .insert(entities[2], Weapon);
+    }
+
+    let mut dispatcher = Dispatcher::new();
+    dispatcher.register(HierarchySystem);
+    dispatcher.dispatch(&world).unwrap();
+    // destroyed behind the hierarchy's back
+    world.fetch::<EntityStorage>().destroy(entities[0]);
+    dispatcher.dispatch(&world).unwrap();
+    world.maintain();
+    dispatcher.dispatch(&world).unwrap();
+
+    let storage = world.fetch::<EntityStorage>();
+    assert!(!storage.is_alive(entities[1]));
+    assert!(!storage.is_alive(entities[2]));
+    assert!(!world.fetch::<MaskedStorage<Weapon>>().contains(entities[2]));
+    assert!(!world.fetch::<MaskedStorage<Parent>>().contains(entities[1]));
+    assert!(world.fetch::<Hierarchy>().all().is_empty());
+}
+
+#[test]
+fn cycles_removed_on_maintain() {
+    let mut world = World::new();
+    world.register_hierarchy();
+    let entities = world.create_entities(2);
+    {
+        let mut parents = world.fetch_mut::<MaskedStorage<Parent>>();
+        parents.insert(entities[0], Parent(entities[1]));
+        parents.insert(entities[1], Parent(entities[0]));
+    }
+
+    let mut dispatcher = Dispatcher::new();
+    dispatcher.register(HierarchySystem);
+    dispatcher.dispatch(&world).unwrap();
+    world.maintain();
+
+    let parents = world.fetch::<MaskedStorage<Parent>>();
+    assert_eq!(parents.get(entities[0]), Some(&Parent(entities[1])));
+    assert!(!parents.contains(entities[1]));
+}
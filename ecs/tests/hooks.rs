@@ -0,0 +1,102 @@
+extern crate ecs;
+
+use std::sync::{Arc, Mutex};
+
+use ecs::command::Commands;
+use ecs::component::storage::{MaskedStorage, VecStorage};
+use ecs::component::Component;
+use ecs::entity::{Entity, EntityStorage};
+use ecs::World;
+
+// Stand-in for a handle owned by some external system.
+#[derive(Clone, Default)]
+struct Voices(Arc<Mutex<Vec<Entity>>>);
+
+struct Sound {
+    voices: Voices,
+}
+
+impl Component for Sound {
+    type Storage = VecStorage<Self>;
+
+    fn on_insert(&mut self, entity: Entity, _commands: &Commands) {
+        self.voices.0.lock().unwrap().push(entity);
+    }
+
+    fn on_remove(&mut self, entity: Entity, commands: &Commands) {
+        self.voices.0.lock().unwrap().retain(|e| *e != entity);
+        commands.insert(entity, Silenced);
+    }
+}
+
+struct Silenced;
+
+impl Component for Silenced {
+    type Storage = VecStorage<Self>;
+}
+
+fn setup() -> (World, Voices) {
+    let mut world = World::new();
+    world.register::<Sound>();
+    world.register::<Silenced>();
+    (world, Voices::default())
+}
+
+#[test]
+fn insert_and_remove() {
+    let (world, voices) = setup();
+    let entity = world.create_entities(1)[0];
+    world.fetch_mut::<MaskedStorage<Sound>>().insert(
+        entity,
+        Sound {
+            voices: voices.clone(),
+        },
+    );
+    assert_eq!(*voices.0.lock().unwrap(), vec![entity]);
+
+    world.fetch_mut::<MaskedStorage<Sound>>().remove(entity);
+    assert!(voices.0.lock().unwrap().is_empty());
+
+    // deferred until maintain
+    assert!(!world.fetch::<MaskedStorage<Silenced>>().contains(entity));
+    world.maintain();
+    assert!(world.fetch::<MaskedStorage<Silenced>>().contains(entity));
+    assert!(world.fetch::<Commands>().is_empty());
+}
+
+#[test]
+fn destroy() {
+    let (world, voices) = setup();
+    let entity = world.create_entities(1)[0];
+    world.fetch_mut::<MaskedStorage<Sound>>().insert(
+        entity,
+        Sound {
+            voices: voices.clone(),
+        },
+    );
+
+    world.destroy(entity);
+    assert!(voices.0.lock().unwrap().is_empty());
+
+    // the hook's insert is dropped along with the entity
+    world.maintain();
+    assert!(!world.fetch::<MaskedStorage<Silenced>>().contains(entity));
+}
+
+#[test]
+fn commands() {
+    let (world, _) = setup();
+    let entity = world.create_entities(1)[0];
+    {
+        let commands = world.fetch::<Commands>();
+        commands.insert(entity, Silenced);
+        commands.destroy(entity);
+        commands.exec(move |world| {
+            world.fetch::<Commands>().remove::<Silenced>(entity);
+        });
+    }
+    world.maintain();
+
+    assert!(!world.fetch::<EntityStorage>().is_alive(entity));
+    assert!(!world.fetch::<MaskedStorage<Silenced>>().contains(entity));
+}
@@ -0,0 +1,85 @@
+extern crate ecs;
+#[macro_use]
+extern crate ecs_derive;
+
+use ecs::component::storage::VecStorage;
+use ecs::component::{Component, WriteStorage};
+use ecs::entity::Entities;
+use ecs::index::ReadIndex;
+use ecs::World;
+
+#[derive(Component)]
+#[Storage(VecStorage)]
+struct Name(String);
+
+#[derive(Component)]
+#[Storage(VecStorage)]
+struct Position(i32, i32);
+
+fn name(name: &Name) -> String {
+    name.0.clone()
+}
+
+fn cell(position: &Position) -> (i32, i32) {
+    (position.0 / 10, position.1 / 10)
+}
+
+#[test]
+fn lookup() {
+    let mut world = World::new();
+    world.register::<Name>();
+    world.register::<Position>();
+    world.register_index(name);
+    world.register_index(cell);
+
+    let player = world.exec(
+        |(entities, mut names, mut positions): (Entities, WriteStorage<Name>, WriteStorage<Position>)| {
+            let player = entities.create();
+            names.insert(player, Name("Player".to_owned()));
+            positions.insert(player, Position(1, 1));
+            let enemy = entities.create();
+            names.insert(enemy, Name("Enemy".to_owned()));
+            positions.insert(enemy, Position(15, 2));
+            player
+        },
+    );
+
+    world.exec(|names: ReadIndex<String, Name>| {
+        assert_eq!(names.get(&"Player".to_owned()), Some(player));
+    });
+
+    world.exec(|mut positions: WriteStorage<Position>| {
+        positions.get_mut(player).unwrap().0 = 12;
+    });
+
+    world.exec(|cells: ReadIndex<(i32, i32), Position>| {
+        assert!(cells.entities(&(0, 0)).is_empty());
+        assert_eq!(cells.entities(&(1, 0)).len(), 2);
+    });
+}
+
+#[test]
+fn synced_by_maintain() {
+    let mut world = World::new();
+    world.register::<Name>();
+    world.register_index(name);
+
+    let entity = world.create_entities(1)[0];
+    world.exec(|mut names: WriteStorage<Name>| {
+        names.insert(entity, Name("Player".to_owned()));
+    });
+    world.maintain();
+
+    world.exec(|index: ReadIndex<String, Name>| {
+        assert_eq!(index.get(&"Player".to_owned()), Some(entity));
+    });
+}
+
+#[test]
+#[should_panic]
+fn with_write_storage() {
+    let mut world = World::new();
+    world.register::<Name>();
+    world.register_index(name);
+    world.exec(|(_names, _index): (WriteStorage<Name>, ReadIndex<String, Name>)| {});
+}
@@ -0,0 +1,95 @@
+extern crate ecs;
+#[macro_use]
+extern crate ecs_derive;
+
+use std::any::TypeId;
+
+use ecs::component::storage::{MaskedStorage, VecStorage};
+use ecs::component::Component;
+use ecs::entity::EntityStorage;
+use ecs::World;
+
+#[derive(Component, Debug)]
+#[Storage(VecStorage)]
+struct Position(i32, i32);
+
+#[derive(Component)]
+#[Storage(VecStorage)]
+struct Velocity(i32, i32);
+
+fn world() -> World {
+    let mut world = World::new();
+    world.register::<Position>();
+    world.register::<Velocity>();
+    world.enable_debug::<Position>();
+    world
+}
+
+#[test]
+fn registered_components() {
+    let world = world();
+    let (first, second) = {
+        let entities = world.fetch::<EntityStorage>();
+        (entities.create(), entities.create())
+    };
+    {
+        let mut positions = world.fetch_mut::<MaskedStorage<Position>>();
+        positions.insert(first, Position(0, 0));
+        positions.insert(second, Position(1, 1));
+        world
+            .fetch_mut::<MaskedStorage<Velocity>>()
+            .insert(first, Velocity(1, 0));
+    }
+
+    let components = world.components();
+    assert_eq!(components.len(), 2);
+    assert_eq!(components[0].id, TypeId::of::<Position>());
+    assert!(components[0].name.ends_with("Position"));
+    assert_eq!(components[0].count, 2);
+    assert!(components[1].name.ends_with("Velocity"));
+    assert_eq!(components[1].count, 1);
+}
+
+#[test]
+fn composition() {
+    let world = world();
+    let entity = world.fetch::<EntityStorage>().create();
+    world
+        .fetch_mut::<MaskedStorage<Velocity>>()
+        .insert(entity, Velocity(1, 0));
+
+    let composition = world.composition(entity);
+    assert_eq!(composition.len(), 1);
+    assert_eq!(composition[0].id, TypeId::of::<Velocity>());
+}
+
+#[test]
+fn dump() {
+    let world = world();
+    let entity = world.fetch::<EntityStorage>().create();
+    world
+        .fetch_mut::<MaskedStorage<Position>>()
+        .insert(entity, Position(1, 2));
+    world
+        .fetch_mut::<MaskedStorage<Velocity>>()
+        .insert(entity, Velocity(1, 0));
+
+    let dump = format!("{:?}", world.dump(entity));
+    assert!(dump.starts_with("Entity(0) {"));
+    assert!(dump.contains("Position: Position(1, 2)"));
+    assert!(dump.contains("Velocity: .."));
+}
+
+#[test]
+#[should_panic]
+fn dump_dead_entity() {
+    let world = world();
+    world.dump(0);
+}
+
+#[test]
+#[should_panic]
+fn debug_unregistered() {
+    let mut world = World::new();
+    world.enable_debug::<Position>();
+}
@@ -19,7 +19,7 @@ impl<'a> System<'a> for MySystem {
     type SystemData = (Entities<'a>, WriteStorage<'a, MyComponent>);
 
     fn run(&mut self, (entities, mut components): Self::SystemData) {
-        for (entity, mut my_component) in (entities, components).join() {
+        for (entity, mut my_component) in (entities, &mut components).join() {
             // okay
         }
     }
@@ -31,5 +31,5 @@ fn registered_component() {
     world.register::<MyComponent>();
     let mut dispatcher = Dispatcher::new();
     dispatcher.register(MySystem);
-    dispatcher.dispatch(&world);
+    dispatcher.dispatch(&world).unwrap();
 }
@@ -0,0 +1,34 @@
+extern crate ecs;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use ecs::resource::FetchLocalMut;
+use ecs::system::System;
+use ecs::{Dispatcher, World};
+
+struct Cache(Rc<RefCell<Vec<i32>>>);
+
+struct CacheSystem;
+
+impl<'a> System<'a> for CacheSystem {
+    type SystemData = FetchLocalMut<'a, Cache>;
+
+    fn run(&mut self, cache: Self::SystemData) {
+        cache.0.borrow_mut().push(1);
+    }
+}
+
+#[test]
+fn dispatch_on_main_thread() {
+    let shared = Rc::new(RefCell::new(Vec::new()));
+    let mut world = World::new();
+    world.add_local_resource(Cache(shared.clone()));
+
+    let mut dispatcher = Dispatcher::new();
+    dispatcher.register(CacheSystem);
+    dispatcher.dispatch(&world).unwrap();
+
+    assert_eq!(*shared.borrow(), vec![1]);
+}
+
@@ -0,0 +1,183 @@
+extern crate ecs;
+#[macro_use]
+extern crate ecs_derive;
+
+use ecs::command::Commands;
+use ecs::component::storage::{MaskedStorage, VecStorage};
+use ecs::component::Component;
+use ecs::entity::{Entity, EntityStorage};
+use ecs::hierarchy::{Hierarchy, Parent};
+use ecs::World;
+
+#[derive(Component, Debug, PartialEq)]
+#[Storage(VecStorage)]
+struct Position(i32);
+
+#[derive(Component, Debug, PartialEq)]
+#[Storage(VecStorage)]
+struct Target(usize);
+
+// Owns a linked entity that goes away with it.
+struct Weapon(Entity);
+
+impl Component for Weapon {
+    type Storage = VecStorage<Self>;
+
+    fn on_remove(&mut self, _entity: Entity, commands: &Commands) {
+        commands.destroy(self.0);
+    }
+}
+
+fn world() -> World {
+    let mut world = World::new();
+    world.register_hierarchy();
+    world.register::<Position>();
+    world.register::<Target>();
+    world.register::<Weapon>();
+    world
+}
+
+#[test]
+fn migrate() {
+    let loading = world();
+    let active = world();
+    // occupy some ids so they differ between worlds
+    active.create_entities(3);
+
+    let entity = loading.create_entities(1)[0];
+    loading
+        .fetch_mut::<MaskedStorage<Position>>()
+        .insert(entity, Position(5));
+
+    let moved = loading.migrate(entity, &active);
+
+    assert_eq!(moved, 3);
+    assert!(!loading.fetch::<EntityStorage>().is_alive(entity));
+    assert!(!loading.fetch::<MaskedStorage<Position>>().contains(entity));
+    assert_eq!(
+        active.fetch::<MaskedStorage<Position>>().get(moved),
+        Some(&Position(5))
+    );
+}
+
+#[test]
+fn remap_references() {
+    let loading = world();
+    let active = world();
+    active.create_entities(2);
+
+    let entities = loading.create_entities(3);
+    {
+        let mut parents = loading.fetch_mut::<MaskedStorage<Parent>>();
+        parents.insert(entities[1], Parent(entities[0]));
+        parents.insert(entities[2], Parent(entities[0]));
+        loading
+            .fetch_mut::<MaskedStorage<Target>>()
+            .insert(entities[2], Target(entities[1]));
+    }
+
+    // entities[0] stays behind
+    let map = loading.migrate_entities(&entities[1..], &active);
+    let (first, second) = (map[&entities[1]], map[&entities[2]]);
+
+    let parents = active.fetch::<MaskedStorage<Parent>>();
+    assert!(!parents.contains(first));
+    assert!(!parents.contains(second));
+
+    let mut targets = active.fetch_mut::<MaskedStorage<Target>>();
+    let target = targets.get_mut(second).unwrap();
+    target.0 = map[&target.0];
+    assert_eq!(*target, Target(first));
+}
+
+#[test]
+fn remap_parents() {
+    let loading = world();
+    let active = world();
+    active.create_entities(2);
+
+    let entities = loading.create_entities(2);
+    loading
+        .fetch_mut::<MaskedStorage<Parent>>()
+        .insert(entities[1], Parent(entities[0]));
+
+    let map = loading.migrate_entities(&entities, &active);
+    let (parent, child) = (map[&entities[0]], map[&entities[1]]);
+
+    assert_eq!(
+        active.fetch::<MaskedStorage<Parent>>().get(child),
+        Some(&Parent(parent))
+    );
+
+    let mut hierarchy = active.fetch_mut::<Hierarchy>();
+    hierarchy.maintain(
+        &active.fetch::<EntityStorage>(),
+        &active.fetch::<MaskedStorage<Parent>>(),
+        &active.fetch::<Commands>(),
+    );
+    assert_eq!(hierarchy.children(parent), &[child]);
+}
+
+#[test]
+fn migrate_descendants() {
+    let loading = world();
+    let active = world();
+
+    let entities = loading.create_entities(3);
+    {
+        let mut parents = loading.fetch_mut::<MaskedStorage<Parent>>();
+        parents.insert(entities[1], Parent(entities[0]));
+        parents.insert(entities[2], Parent(entities[1]));
+    }
+
+    let map = loading.migrate_entities(&entities[..1], &active);
+    assert_eq!(map.len(), 3);
+
+    let storage = loading.fetch::<EntityStorage>();
+    assert!(entities.iter().all(|entity| !storage.is_alive(*entity)));
+    assert!(loading.fetch::<MaskedStorage<Parent>>().is_empty());
+
+    let parents = active.fetch::<MaskedStorage<Parent>>();
+    assert_eq!(parents.get(map[&entities[1]]), Some(&Parent(map[&entities[0]])));
+    assert_eq!(parents.get(map[&entities[2]]), Some(&Parent(map[&entities[1]])));
+}
+
+#[test]
+fn migrate_skips_hooks() {
+    let loading = world();
+    let active = world();
+
+    let entities = loading.create_entities(2);
+    loading
+        .fetch_mut::<MaskedStorage<Weapon>>()
+        .insert(entities[0], Weapon(entities[1]));
+
+    let moved = loading.migrate(entities[0], &active);
+    assert!(loading.fetch::<Commands>().is_empty());
+    assert!(active.fetch::<Commands>().is_empty());
+    loading.maintain();
+
+    assert!(loading.fetch::<EntityStorage>().is_alive(entities[1]));
+    assert!(active.fetch::<MaskedStorage<Weapon>>().contains(moved));
+}
+
+#[test]
+#[should_panic]
+fn migrate_twice() {
+    let loading = world();
+    let active = world();
+    let entity = loading.create_entities(1)[0];
+    loading.migrate_entities(&[entity, entity], &active);
+}
+
+#[test]
+#[should_panic]
+fn migrate_unregistered() {
+    let loading = world();
+    let active = World::new();
+    let entity = loading.create_entities(1)[0];
+    loading
+        .fetch_mut::<MaskedStorage<Position>>()
+        .insert(entity, Position(5));
+    loading.migrate(entity, &active);
+}
@@ -0,0 +1,85 @@
+extern crate ecs;
+#[macro_use]
+extern crate ecs_derive;
+
+use ecs::component::storage::VecStorage;
+use ecs::component::{Component, ReadStorage, WriteStorage};
+use ecs::entity::Entities;
+use ecs::order::ReadOrder;
+use ecs::resource::FetchMut;
+use ecs::system::System;
+use ecs::{Dispatcher, World};
+
+#[derive(Component)]
+#[Storage(VecStorage)]
+struct ZOrder(i32);
+
+#[derive(Component)]
+#[Storage(VecStorage)]
+struct Sprite(&'static str);
+
+fn z(order: &ZOrder) -> i32 {
+    order.0
+}
+
+struct Drawn(Vec<&'static str>);
+
+struct RenderSystem;
+
+impl<'a> System<'a> for RenderSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadOrder<'a, i32, ZOrder>,
+        ReadStorage<'a, Sprite>,
+        FetchMut<'a, Drawn>,
+    );
+
+    fn run(&mut self, (entities, order, sprites, mut drawn): Self::SystemData) {
+        drawn.0.clear();
+        for (_, sprite) in order.join((&entities, &sprites)) {
+            drawn.0.push(sprite.0);
+        }
+    }
+}
+
+#[test]
+fn ordered_join() {
+    let mut world = World::new();
+    world.register::<ZOrder>();
+    world.register::<Sprite>();
+    world.register_order(z);
+    world.add_resource(Drawn(Vec::new()));
+
+    world.exec(
+        |(entities, mut orders, mut sprites): (Entities, WriteStorage<ZOrder>, WriteStorage<Sprite>)| {
+            for &(name, z) in [("background", 0), ("ui", 10), ("player", 5)].iter() {
+                let entity = entities.create();
+                orders.insert(entity, ZOrder(z));
+                sprites.insert(entity, Sprite(name));
+            }
+            // no sprite, skipped by the join
+            let entity = entities.create();
+            orders.insert(entity, ZOrder(1));
+        },
+    );
+
+    let mut dispatcher = Dispatcher::new();
+    dispatcher.register(RenderSystem);
+    dispatcher.dispatch(&world).unwrap();
+    assert_eq!(world.fetch::<Drawn>().0, vec!["background", "player", "ui"]);
+
+    world.exec(|mut orders: WriteStorage<ZOrder>| {
+        orders.get_mut(0).unwrap().0 = 20;
+    });
+    dispatcher.dispatch(&world).unwrap();
+    assert_eq!(world.fetch::<Drawn>().0, vec!["player", "ui", "background"]);
+}
+
+#[test]
+#[should_panic]
+fn with_write_storage() {
+    let mut world = World::new();
+    world.register::<ZOrder>();
+    world.register_order(z);
+    world.exec(|(_orders, _order): (WriteStorage<ZOrder>, ReadOrder<i32, ZOrder>)| {});
+}
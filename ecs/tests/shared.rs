@@ -0,0 +1,51 @@
+extern crate ecs;
+#[macro_use]
+extern crate ecs_derive;
+
+use std::sync::Arc;
+
+use ecs::component::storage::{SharedStorage, VecStorage};
+use ecs::component::{Component, ReadStorage, WriteStorage};
+use ecs::World;
+
+#[derive(Component, Clone, Debug, PartialEq)]
+#[Storage(SharedStorage)]
+struct Material(&'static str);
+
+#[derive(Component, Debug)]
+#[Storage(VecStorage)]
+struct Mesh(usize);
+
+#[test]
+fn shared_components() {
+    let mut world = World::new();
+    world.register::<Material>();
+    world.register::<Mesh>();
+
+    let entities = world.create_entities(3);
+    let stone = Arc::new(Material("stone"));
+    world.exec(|(mut materials, mut meshes): (WriteStorage<Material>, WriteStorage<Mesh>)| {
+        materials.insert_shared(entities[0], stone.clone());
+        materials.insert(entities[1], Material("wood"));
+        materials.insert_shared(entities[2], stone.clone());
+        for (index, entity) in entities.iter().enumerate() {
+            meshes.insert(*entity, Mesh(index));
+        }
+    });
+    assert_eq!(Arc::strong_count(&stone), 3);
+
+    world.exec(|(materials, meshes): (ReadStorage<Material>, ReadStorage<Mesh>)| {
+        let batches: Vec<(&str, Vec<usize>)> = materials
+            .group_by((&meshes,))
+            .into_iter()
+            .map(|(material, meshes)| (material.0, meshes.into_iter().map(|(m,)| m.0).collect()))
+            .collect();
+        assert_eq!(batches, vec![("stone", vec![0, 2]), ("wood", vec![1])]);
+    });
+
+    world.exec(|mut materials: WriteStorage<Material>| {
+        materials.get_mut(entities[2]).unwrap().0 = "moss";
+        assert_eq!(materials.get(entities[0]), Some(&Material("stone")));
+    });
+    assert_eq!(Arc::strong_count(&stone), 2);
+}
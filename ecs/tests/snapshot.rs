@@ -0,0 +1,156 @@
+extern crate ecs;
+#[macro_use]
+extern crate ecs_derive;
+
+use ecs::command::Commands;
+use ecs::component::storage::{MaskedStorage, VecStorage};
+use ecs::component::Component;
+use ecs::entity::{Entity, EntityStorage};
+use ecs::World;
+
+#[derive(Component, Clone, Debug, PartialEq)]
+#[Storage(VecStorage)]
+struct Position(i32);
+
+#[derive(Component)]
+#[Storage(VecStorage)]
+struct Untracked(i32);
+
+// Hooks mirror the component into `Linked`.
+#[derive(Clone)]
+struct Hooked;
+
+impl Component for Hooked {
+    type Storage = VecStorage<Self>;
+
+    fn on_insert(&mut self, entity: Entity, commands: &Commands) {
+        commands.insert(entity, Linked);
+    }
+
+    fn on_remove(&mut self, entity: Entity, commands: &Commands) {
+        commands.remove::<Linked>(entity);
+    }
+}
+
+#[derive(Component)]
+#[Storage(VecStorage)]
+struct Linked;
+
+#[derive(Clone)]
+struct Frame(u32);
+
+struct Settings(u32);
+
+fn world() -> World {
+    let mut world = World::new();
+    world.register::<Position>();
+    world.register::<Untracked>();
+    world.enable_snapshot::<Position>();
+    world.add_resource(Frame(0));
+    world.add_resource(Settings(0));
+    world.enable_resource_snapshot::<Frame>();
+    world
+}
+
+#[test]
+fn rewind() {
+    let world = world();
+    let entities = world.create_entities(2);
+    {
+        let mut positions = world.fetch_mut::<MaskedStorage<Position>>();
+        positions.insert(entities[0], Position(0));
+        positions.insert(entities[1], Position(10));
+    }
+
+    let snapshot = world.snapshot();
+
+    // simulate a few frames
+    let spawned = world.create_entities(1)[0];
+    {
+        let mut positions = world.fetch_mut::<MaskedStorage<Position>>();
+        positions.get_mut(entities[0]).unwrap().0 = 5;
+        positions.insert(spawned, Position(20));
+    }
+    world
+        .fetch_mut::<MaskedStorage<Untracked>>()
+        .insert(spawned, Untracked(20));
+    world.destroy(entities[1]);
+    world.fetch_mut::<Frame>().0 = 3;
+    world.fetch_mut::<Settings>().0 = 1;
+
+    world.restore(&snapshot);
+
+    let storage = world.fetch::<EntityStorage>();
+    assert!(storage.is_alive(entities[0]));
+    assert!(storage.is_alive(entities[1]));
+    assert!(!storage.is_alive(spawned));
+
+    let positions = world.fetch::<MaskedStorage<Position>>();
+    assert_eq!(positions.get(entities[0]), Some(&Position(0)));
+    assert_eq!(positions.get(entities[1]), Some(&Position(10)));
+    assert!(!positions.contains(spawned));
+    // removed even though it isn't part of the snapshot
+    assert!(!world.fetch::<MaskedStorage<Untracked>>().contains(spawned));
+
+    assert_eq!(world.fetch::<Frame>().0, 0);
+    // not part of the snapshot
+    assert_eq!(world.fetch::<Settings>().0, 1);
+}
+
+#[test]
+fn restore_many_times() {
+    let world = world();
+    let entity = world.create_entities(1)[0];
+    world
+        .fetch_mut::<MaskedStorage<Position>>()
+        .insert(entity, Position(1));
+    let snapshot = world.snapshot();
+
+    for _ in 0..3 {
+        world
+            .fetch_mut::<MaskedStorage<Position>>()
+            .get_mut(entity)
+            .unwrap()
+            .0 += 1;
+        world.restore(&snapshot);
+        assert_eq!(
+            world.fetch::<MaskedStorage<Position>>().get(entity),
+            Some(&Position(1))
+        );
+    }
+}
+
+#[test]
+fn restore_skips_hooks_and_commands() {
+    let mut world = World::new();
+    world.register::<Hooked>();
+    world.register::<Linked>();
+    world.enable_snapshot::<Hooked>();
+
+    let entities = world.create_entities(2);
+    world
+        .fetch_mut::<MaskedStorage<Hooked>>()
+        .insert(entities[0], Hooked);
+    world.maintain();
+    let snapshot = world.snapshot();
+
+    world
+        .fetch_mut::<MaskedStorage<Hooked>>()
+        .remove(entities[0]);
+    world
+        .fetch_mut::<MaskedStorage<Hooked>>()
+        .insert(entities[1], Hooked);
+    world.fetch::<Commands>().destroy(entities[0]);
+
+    world.restore(&snapshot);
+    assert!(world.fetch::<Commands>().is_empty());
+    world.maintain();
+
+    assert!(world.fetch::<EntityStorage>().is_alive(entities[0]));
+    let hooked = world.fetch::<MaskedStorage<Hooked>>();
+    assert!(hooked.contains(entities[0]));
+    assert!(!hooked.contains(entities[1]));
+    let linked = world.fetch::<MaskedStorage<Linked>>();
+    assert!(linked.contains(entities[0]));
+    assert!(!linked.contains(entities[1]));
+}
@@ -0,0 +1,113 @@
+#![feature(test)]
+
+extern crate ecs2;
+extern crate test;
+
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use ecs2::aspect::{Aspect, Not};
+use ecs2::component::Component;
+use ecs2::context::Context;
+use ecs2::entity::Entity;
+use ecs2::storage::VecStorage;
+use ecs2::system::System;
+use ecs2::{World, WorldBuilder};
+use test::Bencher;
+
+macro_rules! components {
+    ( $($ty:ident),* ) => {
+        $(
+            struct $ty;
+            impl Component for $ty {
+                type Storage = VecStorage<Self>;
+            }
+        )*
+    };
+}
+
+components!(Moving, C0, C1, C2, C3, C4, C5, C6, C7);
+
+// Only there so its aspect gets registered.
+struct Idle<T: Aspect>(PhantomData<T>);
+
+impl<T> System for Idle<T>
+where
+    T: Aspect,
+{
+    type Aspect = T;
+
+    fn process(&mut self, _context: &mut impl Context, _delta: Duration, _entities: Vec<Entity>) {}
+}
+
+fn idle<T: Aspect>() -> Idle<T> {
+    Idle(PhantomData)
+}
+
+fn builder<'a>() -> WorldBuilder<'a> {
+    let mut builder = WorldBuilder::new();
+    builder.register_component::<Moving>();
+    builder.register_component::<C0>();
+    builder.register_component::<C1>();
+    builder.register_component::<C2>();
+    builder.register_component::<C3>();
+    builder.register_component::<C4>();
+    builder.register_component::<C5>();
+    builder.register_component::<C6>();
+    builder.register_component::<C7>();
+    builder.register_system(idle::<(Moving,)>());
+    builder
+}
+
+// Aspects that never look at `Moving`.
+fn unrelated(builder: &mut WorldBuilder) {
+    builder.register_system(idle::<(C0,)>());
+    builder.register_system(idle::<(C1,)>());
+    builder.register_system(idle::<(C2,)>());
+    builder.register_system(idle::<(C3,)>());
+    builder.register_system(idle::<(C4,)>());
+    builder.register_system(idle::<(C5,)>());
+    builder.register_system(idle::<(C6,)>());
+    builder.register_system(idle::<(C7,)>());
+    builder.register_system(idle::<(C0, C1)>());
+    builder.register_system(idle::<(C2, C3)>());
+    builder.register_system(idle::<(C4, C5)>());
+    builder.register_system(idle::<(C6, C7)>());
+    builder.register_system(idle::<(C0, Not<C1>)>());
+    builder.register_system(idle::<(C2, Not<C3>)>());
+    builder.register_system(idle::<(C4, Not<C5>)>());
+    builder.register_system(idle::<(C6, Not<C7>)>());
+}
+
+fn toggle(b: &mut Bencher, mut world: World) {
+    let entity = {
+        let mut editor = world.create();
+        editor.add(C0);
+        let entity = editor.entity;
+        editor.commit();
+        entity
+    };
+
+    b.iter(|| {
+        {
+            let mut editor = world.editor(entity);
+            editor.add(Moving);
+            editor.commit();
+        }
+        let mut editor = world.editor(entity);
+        editor.remove::<Moving>();
+        editor.commit();
+    });
+}
+
+#[bench]
+fn toggle_one_aspect(b: &mut Bencher) {
+    toggle(b, builder().build());
+}
+
+#[bench]
+fn toggle_with_unrelated_aspects(b: &mut Bencher) {
+    let mut builder = builder();
+    unrelated(&mut builder);
+    toggle(b, builder.build());
+}
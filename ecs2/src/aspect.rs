@@ -1,5 +1,5 @@
 use bit_set::BitSet;
-use component::{Component, ComponentManager};
+use component::{Component, ComponentId, ComponentManager};
 use std::marker::PhantomData;
 
 pub trait Aspect {
@@ -10,10 +10,32 @@ pub trait Aspect {
     fn not(_manager: &ComponentManager) -> BitSet {
         BitSet::new()
     }
+
+    fn one(_manager: &ComponentManager) -> Vec<OneOf> {
+        Vec::new()
+    }
 }
 
 pub struct Not<T: Component>(PhantomData<T>);
 
+// At least one of the aspects in `T` has to hold, a component being there or
+// a `Not` one missing. Each `One` in an aspect has to hold on its own.
+pub struct One<T: Aspect>(PhantomData<T>);
+
+// Conditions of a `One`, holds if any of `present` is there or any of
+// `absent` is missing.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct OneOf {
+    pub present: BitSet,
+    pub absent: BitSet,
+}
+
+impl OneOf {
+    fn check(&self, bits: &BitSet) -> bool {
+        !self.present.is_disjoint(bits) || !self.absent.is_subset(bits)
+    }
+}
+
 impl<T> Aspect for T
 where
     T: Component + 'static,
@@ -36,10 +58,29 @@ where
     }
 }
 
+impl<T> Aspect for One<T>
+where
+    T: Aspect,
+{
+    fn one(manager: &ComponentManager) -> Vec<OneOf> {
+        // nested `One`s only widen this one
+        let mut one = OneOf {
+            present: T::req(manager),
+            absent: T::not(manager),
+        };
+        for nested in T::one(manager) {
+            one.present.union_with(&nested.present);
+            one.absent.union_with(&nested.absent);
+        }
+        vec![one]
+    }
+}
+
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub(crate) struct Matcher {
     req: BitSet,
     not: BitSet,
+    one: Vec<OneOf>,
 }
 
 impl Matcher {
@@ -47,12 +88,28 @@ impl Matcher {
         Matcher {
             req: <T>::req(manager),
             not: <T>::not(manager),
+            one: <T>::one(manager),
         }
     }
 
-    pub fn check(&self, manager: &ComponentManager, bits: &BitSet) -> bool {
-        self.req.intersection(&bits).count() == self.req.len()
-            && self.not.intersection(&bits).count() == 0
+    // Doesn't allocate, it runs for every entity change.
+    pub fn check(&self, _manager: &ComponentManager, bits: &BitSet) -> bool {
+        self.req.is_subset(bits)
+            && self.not.is_disjoint(bits)
+            && self.one.iter().all(|one| one.check(bits))
+    }
+
+    // Components whose presence can change the outcome of `check`.
+    pub fn components<'a>(&'a self) -> impl Iterator<Item = ComponentId> + 'a {
+        self.req
+            .iter()
+            .chain(self.not.iter())
+            .chain(self.one.iter().flat_map(|one| one.present.iter().chain(one.absent.iter())))
+    }
+
+    // Without required components an entity can match with no components at all.
+    pub fn is_unconditional(&self) -> bool {
+        self.req.is_empty()
     }
 }
 
@@ -76,6 +133,14 @@ macro_rules! impl_aspect {
                 $( base.union_with(&<$ty as Aspect>::not(manager)); )*
                 base
             }
+
+            fn one(manager: &ComponentManager) -> Vec<OneOf> {
+                #![allow(unused_variables, non_snake_case)]
+
+                let mut base = Vec::new();
+                $( base.extend(<$ty as Aspect>::one(manager)); )*
+                base
+            }
         }
     };
 }
@@ -130,6 +195,22 @@ mod tests {
         type Storage = VecStorage<Self>;
     }
 
+    #[derive(Default)]
+    struct ThirdComponent;
+    impl Component for ThirdComponent {
+        type Storage = VecStorage<Self>;
+    }
+
+    fn manager() -> ComponentManager {
+        let mut manager = ComponentManager::new();
+        manager.register::<MyComponent>();
+        manager.register::<AnotherComponent>();
+        manager.register::<ThirdComponent>();
+        manager
+    }
+
+    type OneAspect = (MyComponent, One<(AnotherComponent, ThirdComponent)>);
+
     #[test]
     fn req() {
         let mut manager = ComponentManager::new();
@@ -209,4 +290,107 @@ mod tests {
         let matcher = Matcher::new::<(MyComponent, Not<AnotherComponent>)>(&manager);
         assert!(!matcher.check(&manager, &bits));
     }
+
+    #[test]
+    fn one() {
+        let manager = manager();
+
+        let mut expected = OneOf::default();
+        expected.present.insert(manager.id::<AnotherComponent>());
+        expected.present.insert(manager.id::<ThirdComponent>());
+
+        assert_eq!(vec![expected], <OneAspect>::one(&manager));
+        assert_eq!(<MyComponent>::req(&manager), <OneAspect>::req(&manager));
+        assert!(<OneAspect>::not(&manager).is_empty());
+    }
+
+    #[test]
+    fn check_one() {
+        let manager = manager();
+
+        let mut bits = BitSet::new();
+        bits.insert(manager.id::<MyComponent>());
+        bits.insert(manager.id::<ThirdComponent>());
+        let matcher = Matcher::new::<OneAspect>(&manager);
+        assert!(matcher.check(&manager, &bits));
+    }
+
+    #[test]
+    fn check_one_all() {
+        let manager = manager();
+
+        let mut bits = BitSet::new();
+        bits.insert(manager.id::<MyComponent>());
+        bits.insert(manager.id::<AnotherComponent>());
+        bits.insert(manager.id::<ThirdComponent>());
+        let matcher = Matcher::new::<OneAspect>(&manager);
+        assert!(matcher.check(&manager, &bits));
+    }
+
+    #[test]
+    fn check_one_none() {
+        let manager = manager();
+
+        let mut bits = BitSet::new();
+        bits.insert(manager.id::<MyComponent>());
+        let matcher = Matcher::new::<OneAspect>(&manager);
+        assert!(!matcher.check(&manager, &bits));
+    }
+
+    #[test]
+    fn check_one_no_required() {
+        let manager = manager();
+
+        let mut bits = BitSet::new();
+        bits.insert(manager.id::<AnotherComponent>());
+        let matcher = Matcher::new::<OneAspect>(&manager);
+        assert!(!matcher.check(&manager, &bits));
+    }
+
+    #[test]
+    fn check_one_has_excluded() {
+        let manager = manager();
+
+        let mut bits = BitSet::new();
+        bits.insert(manager.id::<AnotherComponent>());
+        bits.insert(manager.id::<ThirdComponent>());
+        let matcher = Matcher::new::<(One<(AnotherComponent,)>, Not<ThirdComponent>)>(&manager);
+        assert!(!matcher.check(&manager, &bits));
+    }
+
+    #[test]
+    fn check_two_ones() {
+        let manager = manager();
+        let matcher =
+            Matcher::new::<(One<(MyComponent,)>, One<(AnotherComponent, ThirdComponent)>)>(&manager);
+
+        let mut bits = BitSet::new();
+        bits.insert(manager.id::<AnotherComponent>());
+        bits.insert(manager.id::<ThirdComponent>());
+        assert!(!matcher.check(&manager, &bits));
+
+        bits.insert(manager.id::<MyComponent>());
+        assert!(matcher.check(&manager, &bits));
+
+        bits.remove(manager.id::<AnotherComponent>());
+        assert!(matcher.check(&manager, &bits));
+    }
+
+    #[test]
+    fn check_one_not() {
+        let manager = manager();
+        let matcher = Matcher::new::<(MyComponent, One<(AnotherComponent, Not<ThirdComponent>)>)>(
+            &manager,
+        );
+
+        let mut bits = BitSet::new();
+        bits.insert(manager.id::<MyComponent>());
+        assert!(matcher.check(&manager, &bits));
+
+        bits.insert(manager.id::<ThirdComponent>());
+        assert!(!matcher.check(&manager, &bits));
+
+        bits.insert(manager.id::<AnotherComponent>());
+        assert!(matcher.check(&manager, &bits));
+    }
 }
@@ -7,9 +7,9 @@ use storage::Storage;
 
 pub trait Component
 where
-    Self: Sized
+    Self: Sized + 'static
 {
-    type Storage: Storage<Type=Self> + Default;
+    type Storage: Storage<Type=Self> + Default + 'static;
 }
 
 pub type ComponentId = usize;
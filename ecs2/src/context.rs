@@ -1,27 +1,40 @@
 use aspect::Aspect;
 use component::Component;
-use entity::{Entity, EntityEditor, EntityManager};
+use entity::{AspectEvent, Entity, EntityBatch, EntityEditor, EntityManager, SubscriptionId};
+use resource::{Fetch, FetchMut, Resource, Resources};
 
 pub trait Context {
     fn create(&mut self) -> EntityEditor;
     fn editor(&mut self, entity: Entity) -> EntityEditor;
+    fn batch(&mut self) -> EntityBatch;
     fn is_alive(&self, entity: Entity) -> bool;
+    // Registers the aspect the first time it is asked for.
+    fn entities<T: Aspect>(&mut self) -> Vec<Entity>;
+    // Panics if the resource was never added or is already borrowed mutably.
+    fn resource<T: Resource>(&self) -> Fetch<T>;
+    fn resource_mut<T: Resource>(&self) -> FetchMut<T>;
 }
 
 pub(crate) struct InternalContext<'a> {
-    entity_manager: &'a mut EntityManager
+    entity_manager: &'a mut EntityManager,
+    resources: &'a Resources
 }
 
 impl<'a> InternalContext<'a> {
-    pub fn new(entity_manager: &'a mut EntityManager) -> Self {
+    pub fn new(entity_manager: &'a mut EntityManager, resources: &'a Resources) -> Self {
         InternalContext {
-            entity_manager
+            entity_manager,
+            resources
         }
     }
 
     pub fn get_entities<T: Aspect>(&self) -> Vec<Entity> {
         self.entity_manager.entities::<T>()
     }
+
+    pub fn events(&mut self, subscription: SubscriptionId) -> Vec<AspectEvent> {
+        self.entity_manager.events(subscription)
+    }
 }
 
 impl<'a> Context for InternalContext<'a> {
@@ -33,7 +46,24 @@ impl<'a> Context for InternalContext<'a> {
         self.entity_manager.editor(entity)
     }
 
+    fn batch(&mut self) -> EntityBatch {
+        self.entity_manager.batch()
+    }
+
     fn is_alive(&self, entity: Entity) -> bool {
         self.entity_manager.is_alive(entity)
     }
+
+    fn entities<T: Aspect>(&mut self) -> Vec<Entity> {
+        self.entity_manager.register::<T>();
+        self.entity_manager.entities::<T>()
+    }
+
+    fn resource<T: Resource>(&self) -> Fetch<T> {
+        self.resources.fetch()
+    }
+
+    fn resource_mut<T: Resource>(&self) -> FetchMut<T> {
+        self.resources.fetch_mut()
+    }
 }
\ No newline at end of file
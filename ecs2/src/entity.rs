@@ -1,15 +1,129 @@
 use bit_set::BitSet;
 use fxhash::FxHashMap;
+use mopa::Any;
 use std::default::Default;
+use std::marker::PhantomData;
+use std::mem;
 
 use aspect::{Aspect, Matcher};
-use component::{Component, ComponentManager};
+use component::{Component, ComponentId, ComponentManager};
+use storage::Storage;
 
 pub type Entity = usize;
 
+// What happens to edits of an editor or batch dropped without `commit`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DropPolicy {
+    Rollback,
+    Commit,
+}
+
+impl Default for DropPolicy {
+    fn default() -> Self {
+        DropPolicy::Rollback
+    }
+}
+
+// A component change waiting for commit.
+trait Staged: Any {
+    fn apply(self: Box<Self>, entity_manager: &mut EntityManager, entity: Entity);
+}
+
+mopafy!(Staged);
+
+struct Added<T: Component>(T);
+
+impl<T> Staged for Added<T>
+where
+    T: Component,
+{
+    fn apply(self: Box<Self>, entity_manager: &mut EntityManager, entity: Entity) {
+        entity_manager.add(entity, self.0);
+    }
+}
+
+struct Removed<T: Component>(PhantomData<T>);
+
+impl<T> Staged for Removed<T>
+where
+    T: Component,
+{
+    fn apply(self: Box<Self>, entity_manager: &mut EntityManager, entity: Entity) {
+        entity_manager.remove::<T>(entity);
+    }
+}
+
+// Staged changes of a single entity, the last change per component wins.
+#[derive(Default)]
+struct Edits {
+    staged: FxHashMap<ComponentId, Box<Staged>>,
+}
+
+impl Edits {
+    fn add<T: Component>(&mut self, entity_manager: &EntityManager, component: T) {
+        let id = entity_manager.component_manager.id::<T>();
+        self.staged.insert(id, Box::new(Added(component)));
+    }
+
+    fn remove<T: Component>(&mut self, entity_manager: &EntityManager) {
+        let id = entity_manager.component_manager.id::<T>();
+        self.staged.insert(id, Box::new(Removed::<T>(PhantomData)));
+    }
+
+    fn contains<T: Component>(&self, entity_manager: &EntityManager, entity: Entity) -> bool {
+        let id = entity_manager.component_manager.id::<T>();
+        match self.staged.get(&id) {
+            Some(staged) => staged.is::<Added<T>>(),
+            None => entity_manager.contains::<T>(entity),
+        }
+    }
+
+    fn get<'a, T: Component>(
+        &'a self,
+        entity_manager: &'a EntityManager,
+        entity: Entity,
+    ) -> Option<&'a T> {
+        let id = entity_manager.component_manager.id::<T>();
+        match self.staged.get(&id) {
+            Some(staged) => staged.downcast_ref::<Added<T>>().map(|added| &added.0),
+            None => entity_manager.get::<T>(entity),
+        }
+    }
+
+    fn get_mut<'a, T: Component>(
+        &'a mut self,
+        entity_manager: &'a mut EntityManager,
+        entity: Entity,
+    ) -> Option<&'a mut T> {
+        let id = entity_manager.component_manager.id::<T>();
+        match self.staged.get_mut(&id) {
+            Some(staged) => staged.downcast_mut::<Added<T>>().map(|added| &mut added.0),
+            None => entity_manager.get_mut::<T>(entity),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.staged.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.staged.clear();
+    }
+
+    // Only touches storages, the index is updated by the caller.
+    fn apply(&mut self, entity_manager: &mut EntityManager, entity: Entity) {
+        for (_, staged) in self.staged.drain() {
+            staged.apply(entity_manager, entity);
+        }
+    }
+}
+
+// Adding and removing components is staged until `commit`. Components that
+// were already committed are changed in place by `get_mut`.
 pub struct EntityEditor<'a> {
     pub entity: Entity,
     entity_manager: &'a mut EntityManager,
+    edits: Edits,
 }
 
 impl<'a> EntityEditor<'a> {
@@ -17,44 +131,158 @@ impl<'a> EntityEditor<'a> {
         EntityEditor {
             entity,
             entity_manager,
+            edits: Default::default(),
         }
     }
 
     pub fn add<T: Component>(&mut self, component: T) {
-        self.entity_manager.add(self.entity, component);
+        self.edits.add(self.entity_manager, component);
     }
 
-    pub fn remove<T: Component>(&mut self) -> T {
-        self.entity_manager.remove(self.entity)
+    pub fn remove<T: Component>(&mut self) {
+        self.edits.remove::<T>(self.entity_manager);
     }
 
     pub fn contains<T: Component>(&self) -> bool {
-        self.entity_manager.contains::<T>(self.entity)
+        self.edits.contains::<T>(self.entity_manager, self.entity)
     }
 
     pub fn get<T: Component>(&self) -> Option<&T> {
-        self.entity_manager.get::<T>(self.entity)
+        self.edits.get::<T>(self.entity_manager, self.entity)
     }
 
+    // Only staged components are covered by `rollback`. A committed one is
+    // changed in place right away, stage a changed copy with `add` to be
+    // able to roll it back.
     pub fn get_mut<T: Component>(&mut self) -> Option<&mut T> {
-        self.entity_manager.get_mut::<T>(self.entity)
+        self.edits.get_mut::<T>(self.entity_manager, self.entity)
     }
 
+    // Staged edits are discarded.
     pub fn destroy(mut self) {
+        self.edits.clear();
         self.entity_manager.destroy(self.entity);
     }
 
     pub fn commit(mut self) {
+        self.apply();
+    }
+
+    pub fn rollback(mut self) {
+        self.edits.clear();
+    }
+
+    fn apply(&mut self) {
+        self.edits.apply(self.entity_manager, self.entity);
         self.entity_manager.commit(self.entity);
     }
 }
 
+impl<'a> Drop for EntityEditor<'a> {
+    fn drop(&mut self) {
+        if !self.edits.is_empty() && self.entity_manager.drop_policy == DropPolicy::Commit {
+            self.apply();
+        }
+    }
+}
+
+// Edits of many entities applied together, the index is only updated once
+// every storage has been changed.
+pub struct EntityBatch<'a> {
+    entity_manager: &'a mut EntityManager,
+    edits: FxHashMap<Entity, Edits>,
+}
+
+impl<'a> EntityBatch<'a> {
+    fn new(entity_manager: &'a mut EntityManager) -> Self {
+        EntityBatch {
+            entity_manager,
+            edits: Default::default(),
+        }
+    }
+
+    pub fn add<T: Component>(&mut self, entity: Entity, component: T) {
+        let entity_manager = &*self.entity_manager;
+        edits(&mut self.edits, entity_manager, entity).add(entity_manager, component);
+    }
+
+    pub fn remove<T: Component>(&mut self, entity: Entity) {
+        let entity_manager = &*self.entity_manager;
+        edits(&mut self.edits, entity_manager, entity).remove::<T>(entity_manager);
+    }
+
+    pub fn contains<T: Component>(&self, entity: Entity) -> bool {
+        match self.edits.get(&entity) {
+            Some(edits) => edits.contains::<T>(self.entity_manager, entity),
+            None => self.entity_manager.contains::<T>(entity),
+        }
+    }
+
+    pub fn commit(mut self) {
+        self.apply();
+    }
+
+    pub fn rollback(mut self) {
+        self.edits.clear();
+    }
+
+    fn apply(&mut self) {
+        let mut entities: Vec<Entity> = self.edits.keys().cloned().collect();
+        entities.sort();
+
+        for entity in entities.iter() {
+            let mut edits = self.edits.remove(entity).expect("Edits not found!");
+            edits.apply(self.entity_manager, *entity);
+        }
+
+        for entity in entities {
+            self.entity_manager.commit(entity);
+        }
+    }
+}
+
+fn edits<'a>(
+    edits: &'a mut FxHashMap<Entity, Edits>,
+    entity_manager: &EntityManager,
+    entity: Entity,
+) -> &'a mut Edits {
+    entity_manager.assert_alive(entity);
+    edits.entry(entity).or_default()
+}
+
+impl<'a> Drop for EntityBatch<'a> {
+    fn drop(&mut self) {
+        if !self.edits.is_empty() && self.entity_manager.drop_policy == DropPolicy::Commit {
+            self.apply();
+        }
+    }
+}
+
+// Lets the manager drop components of a destroyed entity without knowing
+// their types.
+trait AnyStorage: Any {
+    fn discard(&mut self, entity: Entity);
+}
+
+mopafy!(AnyStorage);
+
+impl<T> AnyStorage for T
+where
+    T: Storage + Any,
+{
+    fn discard(&mut self, entity: Entity) {
+        self.remove(entity);
+    }
+}
+
 #[derive(Default)]
 pub(crate) struct EntityManager {
     entity_storage: EntityStorage,
     states: EntityStates,
     index: AspectIndex,
+    storages: FxHashMap<ComponentId, Box<AnyStorage>>,
     pub component_manager: ComponentManager,
+    pub drop_policy: DropPolicy,
 }
 
 impl EntityManager {
@@ -73,12 +301,14 @@ impl EntityManager {
     }
 
     pub fn editor(&mut self, entity: Entity) -> EntityEditor {
-        if !self.entity_storage.is_alive(entity) {
-            panic!("Entity {} not alived!", entity);
-        }
+        self.assert_alive(entity);
         EntityEditor::new(entity, self)
     }
 
+    pub fn batch(&mut self) -> EntityBatch {
+        EntityBatch::new(self)
+    }
+
     pub fn entities<T: Aspect>(&self) -> Vec<Entity> {
         self.index.entities::<T>(&self.component_manager)
     }
@@ -87,33 +317,77 @@ impl EntityManager {
         self.entity_storage.is_alive(entity)
     }
 
+    fn assert_alive(&self, entity: Entity) {
+        if !self.is_alive(entity) {
+            panic!("Entity {} is not alive!", entity);
+        }
+    }
+
     pub fn register<T: Aspect>(&mut self) {
-        self.index.register::<T>(&self.component_manager)
+        self.index.register::<T>(&self.component_manager);
+    }
+
+    pub fn subscribe<T: Aspect>(&mut self) -> SubscriptionId {
+        self.index.subscribe::<T>(&self.component_manager)
+    }
+
+    pub fn events(&mut self, subscription: SubscriptionId) -> Vec<AspectEvent> {
+        self.index.events(subscription)
     }
 
-    // entity manipulation methods
+    pub fn register_component<T: Component>(&mut self) {
+        let id = self.component_manager.register::<T>();
+        self.storages.insert(id, Box::new(T::Storage::default()));
+    }
+
+    fn storage<T: Component>(&self) -> &T::Storage {
+        let id = self.component_manager.id::<T>();
+        self.storages
+            .get(&id)
+            .and_then(|storage| storage.downcast_ref::<T::Storage>())
+            .expect("Storage not found!")
+    }
+
+    fn storage_mut<T: Component>(&mut self) -> &mut T::Storage {
+        let id = self.component_manager.id::<T>();
+        self.storages
+            .get_mut(&id)
+            .and_then(|storage| storage.downcast_mut::<T::Storage>())
+            .expect("Storage not found!")
+    }
+
+    // entity manipulation methods, the index only sees them on commit
     fn add<T: Component>(&mut self, entity: Entity, component: T) {
-        unimplemented!();
+        self.storage_mut::<T>().add(entity, component);
+        T::set_bit(&self.component_manager, self.states.get(entity, false));
     }
 
-    fn remove<T: Component>(&mut self, entity: Entity) -> T {
-        unimplemented!();
+    fn remove<T: Component>(&mut self, entity: Entity) -> Option<T> {
+        T::unset_bit(&self.component_manager, self.states.get(entity, false));
+        self.storage_mut::<T>().remove(entity)
     }
 
     fn contains<T: Component>(&self, entity: Entity) -> bool {
-        unimplemented!();
+        self.states
+            .contains(entity, self.component_manager.id::<T>())
     }
 
     fn get<T: Component>(&self, entity: Entity) -> Option<&T> {
-        unimplemented!();
+        self.storage::<T>().get(entity)
     }
 
     fn get_mut<T: Component>(&mut self, entity: Entity) -> Option<&mut T> {
-        unimplemented!();
+        self.storage_mut::<T>().get_mut(entity)
     }
 
     fn destroy(&mut self, entity: Entity) {
         self.entity_storage.destroy(entity);
+        for id in self.states.get(entity, false).iter() {
+            self.storages
+                .get_mut(&id)
+                .expect("Storage not found!")
+                .discard(entity);
+        }
         // clear state
         self.states.get(entity, true);
         self.index.remove(&self.component_manager, entity);
@@ -142,11 +416,61 @@ impl EntityStates {
 
         self.state.get_mut(&entity).expect("state not found!")
     }
+
+    fn contains(&self, entity: Entity, id: ComponentId) -> bool {
+        self.state
+            .get(&entity)
+            .map(|bits| bits.contains(id))
+            .unwrap_or(false)
+    }
+}
+
+pub(crate) type SubscriptionId = usize;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum AspectEvent {
+    Inserted(Entity),
+    Removed(Entity),
+}
+
+struct IndexEntry {
+    matcher: Matcher,
+    entities: BitSet,
+    subscribers: Vec<SubscriptionId>,
+}
+
+impl IndexEntry {
+    fn insert(&mut self, entity: Entity, queues: &mut [Vec<AspectEvent>]) {
+        if self.entities.insert(entity) {
+            for subscriber in self.subscribers.iter() {
+                queues[*subscriber].push(AspectEvent::Inserted(entity));
+            }
+        }
+    }
+
+    fn remove(&mut self, entity: Entity, queues: &mut [Vec<AspectEvent>]) {
+        if self.entities.remove(entity) {
+            for subscriber in self.subscribers.iter() {
+                queues[*subscriber].push(AspectEvent::Removed(entity));
+            }
+        }
+    }
 }
 
+// Matchers are only re-checked when a component they care about changes,
+// found through `by_component`. `committed` holds the bits of every entity
+// as of its last update.
 #[derive(Default)]
 struct AspectIndex {
-    index: FxHashMap<Matcher, BitSet>,
+    entries: Vec<IndexEntry>,
+    lookup: FxHashMap<Matcher, usize>,
+    by_component: FxHashMap<ComponentId, Vec<usize>>,
+    unconditional: Vec<usize>,
+    committed: FxHashMap<Entity, BitSet>,
+    // one queue per subscriber, drained through `events`
+    queues: Vec<Vec<AspectEvent>>,
+    // scratch space for `update`, kept to avoid allocating
+    affected: BitSet,
 }
 
 impl AspectIndex {
@@ -154,47 +478,118 @@ impl AspectIndex {
         Default::default()
     }
 
-    fn register<T: Aspect>(&mut self, component_manager: &ComponentManager) {
+    // Registering twice is fine. Entities already known to the index are
+    // matched against a new aspect right away.
+    fn register<T: Aspect>(&mut self, component_manager: &ComponentManager) -> usize {
         let matcher = Matcher::new::<T>(component_manager);
-        // TODO: should we fail if it exists?
-        if !self.index.contains_key(&matcher) {
-            self.index.insert(matcher, BitSet::new());
+        if let Some(id) = self.lookup.get(&matcher) {
+            return *id;
+        }
+
+        let mut entities = BitSet::new();
+        for (entity, bits) in self.committed.iter() {
+            if matcher.check(component_manager, bits) {
+                entities.insert(*entity);
+            }
         }
+
+        let id = self.entries.len();
+        for component in matcher.components() {
+            let entries = self.by_component.entry(component).or_default();
+            if !entries.contains(&id) {
+                entries.push(id);
+            }
+        }
+        if matcher.is_unconditional() {
+            self.unconditional.push(id);
+        }
+
+        self.lookup.insert(matcher.clone(), id);
+        self.entries.push(IndexEntry {
+            matcher,
+            entities,
+            subscribers: Vec::new(),
+        });
+        id
+    }
+
+    // Entities already matching are reported as inserted.
+    fn subscribe<T: Aspect>(&mut self, component_manager: &ComponentManager) -> SubscriptionId {
+        let id = self.register::<T>(component_manager);
+        let subscription = self.queues.len();
+        let entry = &mut self.entries[id];
+        entry.subscribers.push(subscription);
+        self.queues
+            .push(entry.entities.iter().map(AspectEvent::Inserted).collect());
+        subscription
+    }
+
+    fn events(&mut self, subscription: SubscriptionId) -> Vec<AspectEvent> {
+        mem::take(&mut self.queues[subscription])
     }
 
     fn update(&mut self, component_manager: &ComponentManager, entity: Entity, bits: &BitSet) {
-        for (matcher, entities) in self.index.iter_mut() {
-            if entities.contains(entity) {
-                if !matcher.check(component_manager, bits) {
-                    entities.remove(entity);
+        self.affected.clear();
+        match self.committed.get(&entity) {
+            Some(old) => {
+                for component in old.symmetric_difference(bits) {
+                    if let Some(entries) = self.by_component.get(&component) {
+                        self.affected.extend(entries.iter().cloned());
+                    }
                 }
-            } else {
-                if matcher.check(component_manager, bits) {
-                    entities.insert(entity);
+            }
+            None => {
+                for component in bits.iter() {
+                    if let Some(entries) = self.by_component.get(&component) {
+                        self.affected.extend(entries.iter().cloned());
+                    }
                 }
+                self.affected.extend(self.unconditional.iter().cloned());
             }
         }
+
+        for id in self.affected.iter() {
+            let entry = &mut self.entries[id];
+            if entry.matcher.check(component_manager, bits) {
+                entry.insert(entity, &mut self.queues);
+            } else {
+                entry.remove(entity, &mut self.queues);
+            }
+        }
+
+        self.committed
+            .entry(entity)
+            .or_default()
+            .clone_from(bits);
     }
 
-    fn remove(&mut self, component_manager: &ComponentManager, entity: Entity) {
-        for (_, entities) in self.index.iter_mut() {
-            entities.remove(entity);
+    fn remove(&mut self, _component_manager: &ComponentManager, entity: Entity) {
+        let old = match self.committed.remove(&entity) {
+            Some(old) => old,
+            None => return,
+        };
+
+        // an entity can only be in a matcher that has one of its components
+        // or doesn't require any
+        for component in old.iter() {
+            if let Some(entries) = self.by_component.get(&component) {
+                for id in entries.iter() {
+                    self.entries[*id].remove(entity, &mut self.queues);
+                }
+            }
+        }
+        for id in self.unconditional.iter() {
+            self.entries[*id].remove(entity, &mut self.queues);
         }
     }
 
     fn entities<T: Aspect>(&self, component_manager: &ComponentManager) -> Vec<Entity> {
         let matcher = Matcher::new::<T>(component_manager);
 
-        if self.index.contains_key(&matcher) {
-            let mut result = Vec::new();
-            let index = self.index.get(&matcher).unwrap();
-            for entity in index.iter() {
-                result.push(entity);
-            }
-            return result;
+        match self.lookup.get(&matcher) {
+            Some(id) => self.entries[*id].entities.iter().collect(),
+            None => panic!("Aspect not registered!"),
         }
-
-        panic!("Aspect not registered!");
     }
 }
 
@@ -248,7 +643,7 @@ trait ComponentSetter {
 
 impl<T> ComponentSetter for T
 where
-    T: Component + 'static,
+    T: Component,
 {
     fn set_bit(component_manager: &ComponentManager, bits: &mut BitSet) {
         bits.insert(component_manager.id::<T>());
@@ -320,10 +715,11 @@ mod tests {
         assert!(!states.get(0, true).contains(1));
     }
 
+    use super::super::aspect::Not;
     use super::super::storage::VecStorage;
 
     #[derive(Default)]
-    struct MyComponent;
+    struct MyComponent(i32);
     impl Component for MyComponent {
         type Storage = VecStorage<Self>;
     }
@@ -446,9 +842,315 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
+    #[should_panic(expected = "is not alive")]
     fn entity_manager_not_existing() {
         let mut entity_manager = EntityManager::new();
         entity_manager.editor(1);
     }
+
+    fn entity_manager() -> EntityManager {
+        let mut entity_manager = EntityManager::new();
+        entity_manager.register_component::<MyComponent>();
+        entity_manager.register_component::<AnotherComponent>();
+        entity_manager.register::<(MyComponent,)>();
+        entity_manager
+    }
+
+    #[test]
+    fn entity_editor_add() {
+        let mut entity_manager = entity_manager();
+        let mut editor = entity_manager.create();
+        editor.add(MyComponent(1));
+
+        assert!(editor.contains::<MyComponent>());
+        assert!(!editor.contains::<AnotherComponent>());
+        assert_eq!(editor.get::<MyComponent>().map(|c| c.0), Some(1));
+    }
+
+    #[test]
+    fn entity_editor_get_mut() {
+        let mut entity_manager = entity_manager();
+        let mut editor = entity_manager.create();
+        editor.add(MyComponent(1));
+        editor.get_mut::<MyComponent>().unwrap().0 = 2;
+
+        assert_eq!(editor.get::<MyComponent>().map(|c| c.0), Some(2));
+        assert!(editor.get_mut::<AnotherComponent>().is_none());
+    }
+
+    #[test]
+    fn entity_editor_remove() {
+        let mut entity_manager = entity_manager();
+        let mut editor = entity_manager.create();
+        editor.add(MyComponent(1));
+        editor.remove::<MyComponent>();
+
+        assert!(!editor.contains::<MyComponent>());
+        assert!(editor.get::<MyComponent>().is_none());
+    }
+
+    #[test]
+    fn entity_editor_remove_committed() {
+        let mut entity_manager = entity_manager();
+        let entity = {
+            let mut editor = entity_manager.create();
+            editor.add(MyComponent(1));
+            let entity = editor.entity;
+            editor.commit();
+            entity
+        };
+
+        let mut editor = entity_manager.editor(entity);
+        editor.remove::<MyComponent>();
+        assert!(!editor.contains::<MyComponent>());
+        // removing twice is fine
+        editor.remove::<MyComponent>();
+        editor.commit();
+        assert!(!entity_manager.editor(entity).contains::<MyComponent>());
+    }
+
+    #[test]
+    fn entity_editor_rollback() {
+        let mut entity_manager = entity_manager();
+        let entity = {
+            let mut editor = entity_manager.create();
+            editor.add(MyComponent(1));
+            editor.entity
+        };
+
+        let editor = entity_manager.editor(entity);
+        assert!(!editor.contains::<MyComponent>());
+    }
+
+    #[test]
+    fn entity_editor_rollback_explicit() {
+        let mut entity_manager = entity_manager();
+        let entity = {
+            let mut editor = entity_manager.create();
+            editor.add(MyComponent(1));
+            let entity = editor.entity;
+            editor.commit();
+            entity
+        };
+
+        let mut editor = entity_manager.editor(entity);
+        editor.add(MyComponent(2));
+        editor.add(AnotherComponent);
+        editor.remove::<MyComponent>();
+        editor.rollback();
+
+        let editor = entity_manager.editor(entity);
+        assert_eq!(editor.get::<MyComponent>().map(|c| c.0), Some(1));
+        assert!(!editor.contains::<AnotherComponent>());
+    }
+
+    #[test]
+    fn entity_editor_drop_commit() {
+        let mut entity_manager = entity_manager();
+        entity_manager.drop_policy = DropPolicy::Commit;
+        let entity = {
+            let mut editor = entity_manager.create();
+            editor.add(MyComponent(1));
+            editor.entity
+        };
+
+        assert_eq!(entity_manager.entities::<(MyComponent,)>(), vec![entity]);
+
+        let mut editor = entity_manager.editor(entity);
+        editor.add(AnotherComponent);
+        editor.rollback();
+        assert!(!entity_manager.editor(entity).contains::<AnotherComponent>());
+    }
+
+    #[test]
+    fn entity_editor_get_mut_staged() {
+        let mut entity_manager = entity_manager();
+        let mut editor = entity_manager.create();
+        editor.add(MyComponent(1));
+        editor.get_mut::<MyComponent>().unwrap().0 = 2;
+        let entity = editor.entity;
+        editor.commit();
+
+        assert_eq!(
+            entity_manager.editor(entity).get::<MyComponent>().map(|c| c.0),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn entity_batch() {
+        let mut entity_manager = entity_manager();
+        let first = entity_manager.create().entity;
+        let second = entity_manager.create().entity;
+
+        {
+            let mut batch = entity_manager.batch();
+            batch.add(first, MyComponent(1));
+            batch.add(second, MyComponent(2));
+            assert!(batch.contains::<MyComponent>(first));
+            batch.rollback();
+        }
+        assert!(entity_manager.entities::<(MyComponent,)>().is_empty());
+
+        {
+            let mut batch = entity_manager.batch();
+            batch.add(first, MyComponent(1));
+            batch.add(second, MyComponent(2));
+            batch.remove::<MyComponent>(second);
+            batch.commit();
+        }
+        assert_eq!(entity_manager.entities::<(MyComponent,)>(), vec![first]);
+    }
+
+    #[test]
+    #[should_panic(expected = "is not alive")]
+    fn entity_batch_not_alive() {
+        let mut entity_manager = entity_manager();
+        entity_manager.batch().add(10, MyComponent(1));
+    }
+
+    #[test]
+    fn entity_editor_commit() {
+        let mut entity_manager = entity_manager();
+        let entity = {
+            let mut editor = entity_manager.create();
+            editor.add(MyComponent(1));
+            editor.entity
+        };
+        // dropped without committing
+        assert!(entity_manager.entities::<(MyComponent,)>().is_empty());
+
+        {
+            let mut editor = entity_manager.editor(entity);
+            editor.add(MyComponent(1));
+            editor.commit();
+        }
+        assert_eq!(entity_manager.entities::<(MyComponent,)>(), vec![entity]);
+
+        {
+            let mut editor = entity_manager.editor(entity);
+            editor.remove::<MyComponent>();
+            editor.commit();
+        }
+        assert!(entity_manager.entities::<(MyComponent,)>().is_empty());
+    }
+
+    #[test]
+    fn entity_editor_destroy() {
+        let mut entity_manager = entity_manager();
+        let entity = {
+            let mut editor = entity_manager.create();
+            editor.add(MyComponent(1));
+            let entity = editor.entity;
+            editor.commit();
+            entity
+        };
+
+        entity_manager.editor(entity).destroy();
+        assert!(!entity_manager.is_alive(entity));
+        assert!(entity_manager.entities::<(MyComponent,)>().is_empty());
+
+        // reused id starts out empty
+        let editor = entity_manager.create();
+        assert_eq!(editor.entity, entity);
+        assert!(!editor.contains::<MyComponent>());
+    }
+
+    #[test]
+    fn entity_manager_register_existing() {
+        let mut entity_manager = entity_manager();
+        let entity = {
+            let mut editor = entity_manager.create();
+            editor.add(MyComponent(1));
+            editor.add(AnotherComponent);
+            let entity = editor.entity;
+            editor.commit();
+            entity
+        };
+        let destroyed = entity_manager.create().entity;
+        entity_manager.editor(destroyed).destroy();
+
+        type Both = (MyComponent, AnotherComponent);
+        entity_manager.register::<Both>();
+        entity_manager.register::<(Not<MyComponent>,)>();
+
+        assert_eq!(entity_manager.entities::<Both>(), vec![entity]);
+        assert!(entity_manager
+            .entities::<(Not<MyComponent>,)>()
+            .is_empty());
+    }
+
+    #[test]
+    fn aspect_index_unrelated_change() {
+        let mut component_manager = ComponentManager::new();
+        component_manager.register::<MyComponent>();
+        component_manager.register::<AnotherComponent>();
+
+        let mut index = AspectIndex::new();
+        type Without = (Not<AnotherComponent>,);
+        index.register::<(MyComponent,)>(&component_manager);
+        index.register::<Without>(&component_manager);
+
+        let entity = 1;
+        let mut bits = BitSet::new();
+        index.update(&component_manager, entity, &bits);
+        assert_eq!(index.entities::<Without>(&component_manager), vec![entity]);
+
+        MyComponent::set_bit(&component_manager, &mut bits);
+        index.update(&component_manager, entity, &bits);
+        assert_eq!(index.entities::<(MyComponent,)>(&component_manager), vec![entity]);
+        assert_eq!(index.entities::<Without>(&component_manager), vec![entity]);
+
+        AnotherComponent::set_bit(&component_manager, &mut bits);
+        index.update(&component_manager, entity, &bits);
+        assert!(index.entities::<Without>(&component_manager).is_empty());
+
+        index.remove(&component_manager, entity);
+        assert!(index.entities::<(MyComponent,)>(&component_manager).is_empty());
+    }
+
+    #[test]
+    fn aspect_index_register_existing() {
+        let mut component_manager = ComponentManager::new();
+        component_manager.register::<MyComponent>();
+        component_manager.register::<AnotherComponent>();
+
+        let mut index = AspectIndex::new();
+        let entity = 1;
+        let mut bits = BitSet::new();
+        MyComponent::set_bit(&component_manager, &mut bits);
+        index.update(&component_manager, entity, &bits);
+
+        index.register::<(MyComponent,)>(&component_manager);
+        assert_eq!(index.entities::<(MyComponent,)>(&component_manager), vec![entity]);
+    }
+
+    #[test]
+    fn aspect_index_events() {
+        let mut component_manager = ComponentManager::new();
+        component_manager.register::<MyComponent>();
+        component_manager.register::<AnotherComponent>();
+
+        let mut index = AspectIndex::new();
+        let mut bits = BitSet::new();
+        MyComponent::set_bit(&component_manager, &mut bits);
+        index.update(&component_manager, 1, &bits);
+
+        let subscription = index.subscribe::<(MyComponent,)>(&component_manager);
+        index.update(&component_manager, 2, &bits);
+        // still matching, nothing to report
+        AnotherComponent::set_bit(&component_manager, &mut bits);
+        index.update(&component_manager, 2, &bits);
+        index.remove(&component_manager, 1);
+
+        assert_eq!(
+            index.events(subscription),
+            vec![
+                AspectEvent::Inserted(1),
+                AspectEvent::Inserted(2),
+                AspectEvent::Removed(1),
+            ]
+        );
+        assert!(index.events(subscription).is_empty());
+    }
 }
@@ -3,12 +3,11 @@ extern crate mopa;
 extern crate bit_set;
 extern crate fxhash;
 
-mod resource;
-
 pub mod aspect;
 pub mod component;
 pub mod context;
 pub mod entity;
+pub mod resource;
 pub mod storage;
 pub mod system;
 
@@ -17,48 +16,110 @@ use std::time::Duration;
 
 use aspect::Aspect;
 use component::Component;
-use entity::EntityManager;
-use system::{System, SystemDispatcher};
+use context::InternalContext;
+use entity::{DropPolicy, Entity, EntityBatch, EntityEditor, EntityManager};
+use resource::{Fetch, FetchMut, Resource, Resources};
+use system::{System, SystemDispatcher, SystemId};
 
 pub struct World<'a> {
     entity_manager: EntityManager,
-    dispatcher: SystemDispatcher<'a>
+    dispatcher: SystemDispatcher<'a>,
+    resources: Resources
 }
 
 impl<'a> World<'a> {
-    fn new(entity_manager: EntityManager, dispatcher: SystemDispatcher<'a>) -> Self {
+    fn new(
+        entity_manager: EntityManager,
+        dispatcher: SystemDispatcher<'a>,
+        resources: Resources
+    ) -> Self {
         World {
             entity_manager,
-            dispatcher
+            dispatcher,
+            resources
         }
     }
+
+    pub fn resource<T: Resource>(&self) -> Fetch<T> {
+        self.resources.fetch()
+    }
+
+    pub fn resource_mut<T: Resource>(&self) -> FetchMut<T> {
+        self.resources.fetch_mut()
+    }
+
+    pub fn create(&mut self) -> EntityEditor {
+        self.entity_manager.create()
+    }
+
+    pub fn editor(&mut self, entity: Entity) -> EntityEditor {
+        self.entity_manager.editor(entity)
+    }
+
+    pub fn batch(&mut self) -> EntityBatch {
+        self.entity_manager.batch()
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entity_manager.is_alive(entity)
+    }
+
+    // Runs every system once, in registration order.
+    pub fn process(&mut self, delta: Duration) {
+        let mut context = InternalContext::new(&mut self.entity_manager, &self.resources);
+        self.dispatcher.dispatch(&mut context, delta);
+    }
+
+    // Runs a system registered with `register_passive_system`.
+    pub fn run_system(&mut self, id: SystemId, delta: Duration) {
+        self.dispatcher
+            .register_passive_aspect(id, &mut self.entity_manager);
+        let mut context = InternalContext::new(&mut self.entity_manager, &self.resources);
+        self.dispatcher.dispatch_passive(id, &mut context, delta);
+    }
 }
 
 pub struct WorldBuilder<'a> {
     entity_manager: EntityManager,
-    dispatcher: SystemDispatcher<'a>
+    dispatcher: SystemDispatcher<'a>,
+    resources: Resources
 }
 
 impl<'a> WorldBuilder<'a> {
     pub fn new() -> Self {
         WorldBuilder {
             entity_manager: Default::default(),
-            dispatcher: Default::default()
+            dispatcher: Default::default(),
+            resources: Default::default()
         }
     }
 
+    pub fn add_resource<R: Resource>(&mut self, resource: R) {
+        self.resources.add(resource);
+    }
+
     pub fn register_component<T>(&mut self)
     where
         T: Component + 'static
     {
-        self.entity_manager.component_manager.register::<T>();
+        self.entity_manager.register_component::<T>();
+    }
+
+    pub fn set_drop_policy(&mut self, policy: DropPolicy) {
+        self.entity_manager.drop_policy = policy;
     }
 
     pub fn register_system(&mut self, system: impl System + 'a) {
         self.dispatcher.register(system)
     }
 
-    pub fn build(self) -> World<'a> {
-        World::new(self.entity_manager, self.dispatcher)
+    // Left out of `World::process`, see `World::run_system`.
+    pub fn register_passive_system(&mut self, system: impl System + 'a) -> SystemId {
+        self.dispatcher.register_passive(system)
+    }
+
+    pub fn build(mut self) -> World<'a> {
+        self.dispatcher.register_aspects(&mut self.entity_manager);
+        World::new(self.entity_manager, self.dispatcher, self.resources)
     }
 }
@@ -5,10 +5,10 @@ use entity::Entity;
 
 pub trait Storage {
     type Type: Component;
-    fn get(&self, entity: Entity) -> &Self::Type;
-    fn get_mut(&mut self, entity: Entity) -> &mut Self::Type;
+    fn get(&self, entity: Entity) -> Option<&Self::Type>;
+    fn get_mut(&mut self, entity: Entity) -> Option<&mut Self::Type>;
     fn add(&mut self, entity: Entity, component: Self::Type);
-    fn remove(&mut self, entity: Entity) -> Self::Type;
+    fn remove(&mut self, entity: Entity) -> Option<Self::Type>;
 }
 
 mod vec;
@@ -1,9 +1,7 @@
-use std::mem;
-
 use super::*;
 
 pub struct VecStorage<T: Component> {
-    components: Vec<T>,
+    components: Vec<Option<T>>,
 }
 
 impl<T> VecStorage<T>
@@ -15,19 +13,14 @@ where
     }
 }
 
-const GROWTH_FACTOR: usize = 10;
-
 impl<T> Default for VecStorage<T>
 where
     T: Component,
 {
     fn default() -> Self {
-        let mut components = Vec::with_capacity(GROWTH_FACTOR);
-        unsafe {
-            components.set_len(GROWTH_FACTOR);
+        VecStorage {
+            components: Vec::new(),
         }
-
-        VecStorage { components }
     }
 }
 
@@ -37,35 +30,39 @@ where
 {
     type Type = T;
 
-    fn get(&self, entity: Entity) -> &T {
-        &self.components[entity]
+    fn get(&self, entity: Entity) -> Option<&T> {
+        self.components
+            .get(entity)
+            .and_then(|component| component.as_ref())
     }
 
-    fn get_mut(&mut self, entity: Entity) -> &mut T {
-        &mut self.components[entity]
+    fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        self.components
+            .get_mut(entity)
+            .and_then(|component| component.as_mut())
     }
 
     fn add(&mut self, entity: Entity, component: T) {
-        let index = entity;
-        if self.components.len() <= index {
-            unsafe {
-                let size = index + GROWTH_FACTOR;
-                self.components.reserve(size);
-                self.components.set_len(size);
-            }
+        let len = self.components.len();
+        if len <= entity {
+            self.components.extend((len..entity + 1).map(|_| None));
         }
 
-        mem::replace(&mut self.components[index], component);
+        self.components[entity] = Some(component);
     }
 
-    fn remove(&mut self, entity: Entity) -> T {
-        unsafe { mem::replace(&mut self.components[entity], mem::zeroed()) }
+    fn remove(&mut self, entity: Entity) -> Option<T> {
+        self.components
+            .get_mut(entity)
+            .and_then(|component| component.take())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
 
     #[derive(Default)]
     struct MyComponent(i32);
@@ -73,6 +70,18 @@ mod tests {
         type Storage = VecStorage<Self>;
     }
 
+    // Counts how many times it has been dropped.
+    struct Tracked(Rc<Cell<usize>>);
+    impl Component for Tracked {
+        type Storage = VecStorage<Self>;
+    }
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
     #[test]
     fn get_existing() {
         let entity = 1;
@@ -80,16 +89,17 @@ mod tests {
 
         storage.add(entity, MyComponent(1));
 
-        let component = storage.get(entity);
-        assert_eq!(1, component.0);
+        assert_eq!(Some(1), storage.get(entity).map(|c| c.0));
     }
 
     #[test]
     fn get_not_existing() {
-        let entity = 1;
         let mut storage: VecStorage<MyComponent> = VecStorage::new();
-        // data maybe all zeroes
-        storage.get(entity);
+        storage.add(3, MyComponent(3));
+
+        assert!(storage.get(1).is_none());
+        assert!(storage.get(10).is_none());
+        assert!(storage.get_mut(1).is_none());
     }
 
     #[test]
@@ -98,9 +108,46 @@ mod tests {
         let mut storage: VecStorage<MyComponent> = VecStorage::new();
 
         storage.add(entity, MyComponent(1));
-        storage.remove(entity);
+        assert_eq!(Some(1), storage.remove(entity).map(|c| c.0));
+
+        assert!(storage.get(entity).is_none());
+        assert!(storage.remove(entity).is_none());
+    }
 
-        // data maybe all zeroes
-        storage.get(entity);
+    #[test]
+    fn get_mut() {
+        let mut storage: VecStorage<MyComponent> = VecStorage::new();
+        storage.add(0, MyComponent(1));
+        storage.get_mut(0).unwrap().0 = 2;
+
+        assert_eq!(Some(2), storage.get(0).map(|c| c.0));
+    }
+
+    #[test]
+    fn reuse() {
+        let mut storage: VecStorage<MyComponent> = VecStorage::new();
+        storage.add(1, MyComponent(1));
+        storage.remove(1);
+        storage.add(1, MyComponent(2));
+
+        assert_eq!(Some(2), storage.get(1).map(|c| c.0));
+    }
+
+    #[test]
+    fn drops() {
+        let drops = Rc::new(Cell::new(0));
+        {
+            let mut storage: VecStorage<Tracked> = VecStorage::new();
+            storage.add(0, Tracked(drops.clone()));
+            storage.add(2, Tracked(drops.clone()));
+
+            // replaced
+            storage.add(0, Tracked(drops.clone()));
+            assert_eq!(1, drops.get());
+
+            storage.remove(2);
+            assert_eq!(2, drops.get());
+        }
+        assert_eq!(3, drops.get());
     }
 }
@@ -1,34 +1,185 @@
+use fxhash::FxHashMap;
 use std::default::Default;
 use std::time::Duration;
 
 use aspect::Aspect;
 use context::{Context, InternalContext};
-use entity::Entity;
+use entity::{AspectEvent, Entity, EntityManager, SubscriptionId};
 
 pub trait System {
     type Aspect: Aspect;
 
     fn process(&mut self, context: &mut impl Context, duration: Duration, entities: Vec<Entity>);
+
+    // Called before `process` for every entity that started matching the
+    // aspect since the last run.
+    fn inserted(&mut self, _context: &mut impl Context, _entity: Entity) {}
+
+    // Like `inserted`, the entity may already be destroyed by then.
+    fn removed(&mut self, _context: &mut impl Context, _entity: Entity) {}
+}
+
+// Runs the inner system once every `interval` of accumulated time, passing
+// `interval` as its duration. Catches up with several runs when a frame takes
+// longer than that.
+pub struct Interval<S: System> {
+    system: S,
+    interval: Duration,
+    elapsed: Duration,
+}
+
+impl<S> Interval<S>
+where
+    S: System,
+{
+    pub fn new(system: S, interval: Duration) -> Self {
+        if interval == Duration::from_secs(0) {
+            panic!("Interval can't be zero!");
+        }
+
+        Interval {
+            system,
+            interval,
+            elapsed: Duration::from_secs(0),
+        }
+    }
+}
+
+impl<S> System for Interval<S>
+where
+    S: System,
+{
+    type Aspect = S::Aspect;
+
+    fn process(&mut self, context: &mut impl Context, duration: Duration, entities: Vec<Entity>) {
+        self.elapsed += duration;
+        let mut entities = Some(entities);
+        while self.elapsed >= self.interval {
+            self.elapsed -= self.interval;
+            // earlier runs may have changed which entities match
+            let entities = match entities.take() {
+                Some(entities) => entities,
+                None => context.entities::<S::Aspect>(),
+            };
+            self.system.process(context, self.interval, entities);
+        }
+    }
+
+    fn inserted(&mut self, context: &mut impl Context, entity: Entity) {
+        self.system.inserted(context, entity);
+    }
+
+    fn removed(&mut self, context: &mut impl Context, entity: Entity) {
+        self.system.removed(context, entity);
+    }
+}
+
+pub trait DelayedSystem {
+    type Aspect: Aspect;
+
+    // How long after it starts matching the entity expires.
+    fn delay(&mut self, context: &mut impl Context, entity: Entity) -> Duration;
+
+    fn expired(&mut self, context: &mut impl Context, entity: Entity);
+}
+
+// Expires every entity once, it has to stop and start matching the aspect
+// again to get a new delay.
+pub struct Delayed<S: DelayedSystem> {
+    system: S,
+    remaining: FxHashMap<Entity, Duration>,
+}
+
+impl<S> Delayed<S>
+where
+    S: DelayedSystem,
+{
+    pub fn new(system: S) -> Self {
+        Delayed {
+            system,
+            remaining: Default::default(),
+        }
+    }
+}
+
+impl<S> System for Delayed<S>
+where
+    S: DelayedSystem,
+{
+    type Aspect = S::Aspect;
+
+    fn process(&mut self, context: &mut impl Context, duration: Duration, entities: Vec<Entity>) {
+        let mut expired = Vec::new();
+        for entity in entities {
+            if let Some(remaining) = self.remaining.get_mut(&entity) {
+                if *remaining <= duration {
+                    expired.push(entity);
+                } else {
+                    *remaining -= duration;
+                }
+            }
+        }
+
+        for entity in expired {
+            self.remaining.remove(&entity);
+            self.system.expired(context, entity);
+        }
+    }
+
+    fn inserted(&mut self, context: &mut impl Context, entity: Entity) {
+        let delay = self.system.delay(context, entity);
+        self.remaining.insert(entity, delay);
+    }
+
+    fn removed(&mut self, _context: &mut impl Context, entity: Entity) {
+        self.remaining.remove(&entity);
+    }
 }
 
+// Handle to a passive system, see `World::run_system`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SystemId(usize);
+
 trait Executor {
+    fn register(&mut self, entity_manager: &mut EntityManager);
     fn execute(&mut self, context: &mut InternalContext, duration: Duration);
 }
 
-impl<T, K> Executor for K
+struct Subscriber<K> {
+    system: K,
+    subscription: Option<SubscriptionId>,
+}
+
+impl<T, K> Executor for Subscriber<K>
 where
     T: Aspect,
     K: System<Aspect = T>,
 {
+    fn register(&mut self, entity_manager: &mut EntityManager) {
+        if self.subscription.is_none() {
+            self.subscription = Some(entity_manager.subscribe::<T>());
+        }
+    }
+
     fn execute(&mut self, context: &mut InternalContext, duration: Duration) {
+        let subscription = self.subscription.expect("Aspect not registered!");
+        for event in context.events(subscription) {
+            match event {
+                AspectEvent::Inserted(entity) => self.system.inserted(context, entity),
+                AspectEvent::Removed(entity) => self.system.removed(context, entity),
+            }
+        }
+
         let entities = context.get_entities::<T>();
-        self.process(context, duration, entities)
+        self.system.process(context, duration, entities)
     }
 }
 
 #[derive(Default)]
 pub(crate) struct SystemDispatcher<'a> {
     systems: Vec<Box<Executor + 'a>>,
+    // only run when asked for
+    passive: Vec<Box<Executor + 'a>>,
 }
 
 impl<'a> SystemDispatcher<'a> {
@@ -37,7 +188,40 @@ impl<'a> SystemDispatcher<'a> {
     }
 
     pub fn register(&mut self, system: impl System + 'a) {
-        self.systems.push(Box::new(system))
+        self.systems.push(Box::new(Subscriber {
+            system,
+            subscription: None,
+        }))
+    }
+
+    pub fn register_passive(&mut self, system: impl System + 'a) -> SystemId {
+        self.passive.push(Box::new(Subscriber {
+            system,
+            subscription: None,
+        }));
+        SystemId(self.passive.len() - 1)
+    }
+
+    pub fn register_aspects(&mut self, entity_manager: &mut EntityManager) {
+        for system in self.systems.iter_mut() {
+            system.register(entity_manager);
+        }
+    }
+
+    // Passive systems subscribe on their first run, so ones that never run
+    // don't pile up events.
+    pub fn register_passive_aspect(&mut self, id: SystemId, entity_manager: &mut EntityManager) {
+        self.passive
+            .get_mut(id.0)
+            .expect("System not found!")
+            .register(entity_manager);
+    }
+
+    pub fn dispatch_passive(&mut self, id: SystemId, context: &mut InternalContext, duration: Duration) {
+        self.passive
+            .get_mut(id.0)
+            .expect("System not found!")
+            .execute(context, duration);
     }
 
     pub fn dispatch(&mut self, context: &mut InternalContext, duration: Duration) {
@@ -54,6 +238,7 @@ mod tests {
     use aspect::{Aspect, Not};
     use component::{Component, ComponentManager};
     use entity::{Entity, EntityManager};
+    use resource::Resources;
 
     #[derive(Default)]
     struct MyComponent;
@@ -103,13 +288,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn dispatch_matching() {
+        let mut entity_manager = EntityManager::new();
+        entity_manager.register_component::<MyComponent>();
+        entity_manager.register_component::<AnotherComponent>();
+        {
+            let mut editor = entity_manager.create();
+            editor.add(MyComponent);
+            editor.commit();
+        }
+        {
+            let mut editor = entity_manager.create();
+            editor.add(MyComponent);
+            editor.add(AnotherComponent);
+            editor.commit();
+        }
+        let mut dispatcher = SystemDispatcher::new();
+        dispatcher.register(MySystem);
+        dispatcher.register_aspects(&mut entity_manager);
+        let resources = Resources::new();
+        let mut context = InternalContext::new(&mut entity_manager, &resources);
+
+        dispatcher.dispatch(&mut context, Duration::from_millis(100));
+    }
+
     #[test]
     #[should_panic]
     fn dispatch() {
         let mut entity_manager = EntityManager::new();
         entity_manager.component_manager.register::<MyComponent>();
         entity_manager.component_manager.register::<AnotherComponent>();
-        let mut context = InternalContext::new(&mut entity_manager);
+        let resources = Resources::new();
+        let mut context = InternalContext::new(&mut entity_manager, &resources);
 
         let mut dispatcher = SystemDispatcher::new();
         dispatcher.register(PanickingSystem);
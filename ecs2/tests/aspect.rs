@@ -0,0 +1,74 @@
+extern crate ecs2;
+
+use std::time::Duration;
+
+use ecs2::aspect::Not;
+use ecs2::component::Component;
+use ecs2::context::Context;
+use ecs2::entity::Entity;
+use ecs2::storage::VecStorage;
+use ecs2::system::System;
+use ecs2::WorldBuilder;
+
+struct Enemy;
+impl Component for Enemy {
+    type Storage = VecStorage<Self>;
+}
+
+struct Boss;
+impl Component for Boss {
+    type Storage = VecStorage<Self>;
+}
+
+// Counts what it was given and what it looked up by itself.
+struct Census {
+    enemies: usize,
+    minions: usize,
+}
+
+impl System for Census {
+    type Aspect = (Enemy,);
+
+    fn process(&mut self, context: &mut impl Context, _delta: Duration, entities: Vec<Entity>) {
+        self.enemies = entities.len();
+        self.minions = context.entities::<(Enemy, Not<Boss>)>().len();
+    }
+}
+
+impl<'a> System for &'a mut Census {
+    type Aspect = (Enemy,);
+
+    fn process(&mut self, context: &mut impl Context, delta: Duration, entities: Vec<Entity>) {
+        (**self).process(context, delta, entities)
+    }
+}
+
+#[test]
+fn registered_at_build() {
+    let mut census = Census {
+        enemies: 0,
+        minions: 0,
+    };
+    {
+        let mut builder = WorldBuilder::new();
+        // components come after the system that uses them
+        builder.register_system(&mut census);
+        builder.register_component::<Enemy>();
+        builder.register_component::<Boss>();
+        let mut world = builder.build();
+
+        for boss in vec![false, false, true] {
+            let mut editor = world.create();
+            editor.add(Enemy);
+            if boss {
+                editor.add(Boss);
+            }
+            editor.commit();
+        }
+
+        world.process(Duration::from_millis(16));
+    }
+
+    assert_eq!(census.enemies, 3);
+    assert_eq!(census.minions, 2);
+}
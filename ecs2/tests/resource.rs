@@ -0,0 +1,90 @@
+extern crate ecs2;
+
+use std::time::Duration;
+
+use ecs2::component::Component;
+use ecs2::context::Context;
+use ecs2::entity::Entity;
+use ecs2::storage::VecStorage;
+use ecs2::system::System;
+use ecs2::WorldBuilder;
+
+#[derive(Default)]
+struct Time {
+    elapsed: Duration,
+    frames: usize,
+}
+
+struct Input {
+    jump: bool,
+}
+
+struct Player;
+impl Component for Player {
+    type Storage = VecStorage<Self>;
+}
+
+struct Jumping;
+impl Component for Jumping {
+    type Storage = VecStorage<Self>;
+}
+
+struct Clock;
+impl System for Clock {
+    type Aspect = (Player,);
+
+    fn process(&mut self, context: &mut impl Context, delta: Duration, _entities: Vec<Entity>) {
+        let mut time = context.resource_mut::<Time>();
+        time.elapsed += delta;
+        time.frames += 1;
+    }
+}
+
+struct Jump;
+impl System for Jump {
+    type Aspect = (Player,);
+
+    fn process(&mut self, context: &mut impl Context, _delta: Duration, entities: Vec<Entity>) {
+        let jump = context.resource::<Input>().jump;
+        if !jump {
+            return;
+        }
+
+        for entity in entities {
+            let mut editor = context.editor(entity);
+            editor.add(Jumping);
+            editor.commit();
+        }
+    }
+}
+
+#[test]
+fn resources() {
+    let mut builder = WorldBuilder::new();
+    builder.register_component::<Player>();
+    builder.register_component::<Jumping>();
+    builder.add_resource(Time::default());
+    builder.add_resource(Input { jump: false });
+    builder.register_system(Clock);
+    builder.register_system(Jump);
+    let mut world = builder.build();
+
+    let player = {
+        let mut editor = world.create();
+        editor.add(Player);
+        let entity = editor.entity;
+        editor.commit();
+        entity
+    };
+
+    world.process(Duration::from_millis(10));
+    assert!(!world.editor(player).contains::<Jumping>());
+
+    world.resource_mut::<Input>().jump = true;
+    world.process(Duration::from_millis(10));
+    assert!(world.editor(player).contains::<Jumping>());
+
+    let time = world.resource::<Time>();
+    assert_eq!(time.frames, 2);
+    assert_eq!(time.elapsed, Duration::from_millis(20));
+}
@@ -0,0 +1,91 @@
+extern crate ecs2;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use ecs2::component::Component;
+use ecs2::context::Context;
+use ecs2::entity::Entity;
+use ecs2::storage::VecStorage;
+use ecs2::system::System;
+use ecs2::WorldBuilder;
+
+struct Body;
+impl Component for Body {
+    type Storage = VecStorage<Self>;
+}
+
+#[derive(Debug, PartialEq)]
+enum Call {
+    Inserted(Entity),
+    Removed(Entity),
+    Process(Vec<Entity>),
+}
+
+struct Physics {
+    calls: Rc<RefCell<Vec<Call>>>,
+}
+
+impl System for Physics {
+    type Aspect = (Body,);
+
+    fn process(&mut self, _context: &mut impl Context, _delta: Duration, entities: Vec<Entity>) {
+        self.calls.borrow_mut().push(Call::Process(entities));
+    }
+
+    fn inserted(&mut self, context: &mut impl Context, entity: Entity) {
+        assert!(context.editor(entity).contains::<Body>());
+        self.calls.borrow_mut().push(Call::Inserted(entity));
+    }
+
+    fn removed(&mut self, _context: &mut impl Context, entity: Entity) {
+        self.calls.borrow_mut().push(Call::Removed(entity));
+    }
+}
+
+#[test]
+fn inserted_and_removed() {
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let mut builder = WorldBuilder::new();
+    builder.register_component::<Body>();
+    builder.register_system(Physics {
+        calls: calls.clone(),
+    });
+    let mut world = builder.build();
+
+    let (first, second) = {
+        let mut spawn = || {
+            let mut editor = world.create();
+            editor.add(Body);
+            let entity = editor.entity;
+            editor.commit();
+            entity
+        };
+        (spawn(), spawn())
+    };
+    world.process(Duration::from_millis(16));
+    // nothing changed
+    world.process(Duration::from_millis(16));
+
+    {
+        let mut editor = world.editor(first);
+        editor.remove::<Body>();
+        editor.commit();
+    }
+    world.editor(second).destroy();
+    world.process(Duration::from_millis(16));
+
+    assert_eq!(
+        *calls.borrow(),
+        vec![
+            Call::Inserted(first),
+            Call::Inserted(second),
+            Call::Process(vec![first, second]),
+            Call::Process(vec![first, second]),
+            Call::Removed(first),
+            Call::Removed(second),
+            Call::Process(vec![]),
+        ]
+    );
+}
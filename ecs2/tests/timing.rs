@@ -0,0 +1,173 @@
+extern crate ecs2;
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use ecs2::component::Component;
+use ecs2::context::Context;
+use ecs2::entity::Entity;
+use ecs2::storage::VecStorage;
+use ecs2::system::{Delayed, DelayedSystem, Interval, System};
+use ecs2::{World, WorldBuilder};
+
+struct Spawner;
+impl Component for Spawner {
+    type Storage = VecStorage<Self>;
+}
+
+struct Expiry(Duration);
+impl Component for Expiry {
+    type Storage = VecStorage<Self>;
+}
+
+struct Count {
+    runs: Rc<Cell<usize>>,
+    last: Rc<Cell<Duration>>,
+}
+
+impl System for Count {
+    type Aspect = (Spawner,);
+
+    fn process(&mut self, _context: &mut impl Context, delta: Duration, _entities: Vec<Entity>) {
+        self.runs.set(self.runs.get() + 1);
+        self.last.set(delta);
+    }
+}
+
+fn count() -> (Count, Rc<Cell<usize>>, Rc<Cell<Duration>>) {
+    let runs = Rc::new(Cell::new(0));
+    let last = Rc::new(Cell::new(Duration::from_secs(0)));
+    let count = Count {
+        runs: runs.clone(),
+        last: last.clone(),
+    };
+    (count, runs, last)
+}
+
+// Destroys entities once their expiry is up.
+struct Reaper;
+impl DelayedSystem for Reaper {
+    type Aspect = (Expiry,);
+
+    fn delay(&mut self, context: &mut impl Context, entity: Entity) -> Duration {
+        context.editor(entity).get::<Expiry>().unwrap().0
+    }
+
+    fn expired(&mut self, context: &mut impl Context, entity: Entity) {
+        context.editor(entity).destroy();
+    }
+}
+
+fn builder<'a>() -> WorldBuilder<'a> {
+    let mut builder = WorldBuilder::new();
+    builder.register_component::<Spawner>();
+    builder.register_component::<Expiry>();
+    builder
+}
+
+#[test]
+fn interval() {
+    let (system, runs, last) = count();
+    let mut builder = builder();
+    builder.register_system(Interval::new(system, Duration::from_millis(100)));
+    let mut world = builder.build();
+
+    for _ in 0..5 {
+        world.process(Duration::from_millis(30));
+    }
+    assert_eq!(runs.get(), 1);
+    assert_eq!(last.get(), Duration::from_millis(100));
+
+    // leftover time carries over
+    world.process(Duration::from_millis(50));
+    assert_eq!(runs.get(), 2);
+}
+
+#[test]
+fn interval_catches_up() {
+    let (system, runs, last) = count();
+    let mut builder = builder();
+    builder.register_system(Interval::new(system, Duration::from_millis(100)));
+    let mut world = builder.build();
+
+    world.process(Duration::from_millis(350));
+    assert_eq!(runs.get(), 3);
+    assert_eq!(last.get(), Duration::from_millis(100));
+
+    world.process(Duration::from_millis(50));
+    assert_eq!(runs.get(), 4);
+}
+
+#[test]
+fn delayed() {
+    let mut builder = builder();
+    builder.register_system(Delayed::new(Reaper));
+    let mut world = builder.build();
+
+    let spawn = |world: &mut World, expiry: u64| {
+        let mut editor = world.create();
+        editor.add(Expiry(Duration::from_millis(expiry)));
+        let entity = editor.entity;
+        editor.commit();
+        entity
+    };
+    let short = spawn(&mut world, 100);
+    let long = spawn(&mut world, 250);
+
+    world.process(Duration::from_millis(100));
+    assert!(!world.is_alive(short));
+    assert!(world.is_alive(long));
+
+    world.process(Duration::from_millis(100));
+    assert!(world.is_alive(long));
+    world.process(Duration::from_millis(100));
+    assert!(!world.is_alive(long));
+}
+
+#[test]
+fn passive() {
+    let (system, runs, last) = count();
+    let mut builder = builder();
+    let id = builder.register_passive_system(system);
+    let mut world = builder.build();
+
+    world.process(Duration::from_millis(16));
+    assert_eq!(runs.get(), 0);
+
+    world.run_system(id, Duration::from_millis(5));
+    assert_eq!(runs.get(), 1);
+    assert_eq!(last.get(), Duration::from_millis(5));
+}
+
+struct Seen(Rc<Cell<usize>>);
+
+impl System for Seen {
+    type Aspect = (Spawner,);
+
+    fn process(&mut self, _context: &mut impl Context, _delta: Duration, _entities: Vec<Entity>) {}
+
+    fn inserted(&mut self, _context: &mut impl Context, _entity: Entity) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+#[test]
+fn passive_sees_existing_entities() {
+    let seen = Rc::new(Cell::new(0));
+    let mut builder = builder();
+    let id = builder.register_passive_system(Seen(seen.clone()));
+    let mut world = builder.build();
+
+    for _ in 0..2 {
+        let mut editor = world.create();
+        editor.add(Spawner);
+        editor.commit();
+    }
+    world.process(Duration::from_millis(16));
+
+    world.run_system(id, Duration::from_millis(16));
+    assert_eq!(seen.get(), 2);
+    world.run_system(id, Duration::from_millis(16));
+    assert_eq!(seen.get(), 2);
+}
@@ -0,0 +1,139 @@
+extern crate ecs2;
+
+use std::time::Duration;
+
+use ecs2::aspect::Not;
+use ecs2::component::Component;
+use ecs2::context::Context;
+use ecs2::entity::{DropPolicy, Entity, EntityEditor};
+use ecs2::storage::VecStorage;
+use ecs2::system::System;
+use ecs2::WorldBuilder;
+
+#[derive(Debug, PartialEq)]
+struct Position(f32);
+impl Component for Position {
+    type Storage = VecStorage<Self>;
+}
+
+struct Velocity(f32);
+impl Component for Velocity {
+    type Storage = VecStorage<Self>;
+}
+
+struct Frozen;
+impl Component for Frozen {
+    type Storage = VecStorage<Self>;
+}
+
+struct Movement;
+impl System for Movement {
+    type Aspect = (Position, Velocity, Not<Frozen>);
+
+    fn process(&mut self, context: &mut impl Context, delta: Duration, entities: Vec<Entity>) {
+        let seconds = delta.as_secs() as f32 + delta.subsec_nanos() as f32 / 1e9;
+        for entity in entities {
+            let mut editor = context.editor(entity);
+            let velocity = editor.get::<Velocity>().unwrap().0;
+            editor.get_mut::<Position>().unwrap().0 += velocity * seconds;
+        }
+    }
+}
+
+// Destroys entities once they move past the bounds.
+struct Bounds;
+impl System for Bounds {
+    type Aspect = (Position,);
+
+    fn process(&mut self, context: &mut impl Context, _delta: Duration, entities: Vec<Entity>) {
+        for entity in entities {
+            let editor = context.editor(entity);
+            if editor.get::<Position>().unwrap().0 > 10.0 {
+                editor.destroy();
+            }
+        }
+    }
+}
+
+fn builder<'a>() -> WorldBuilder<'a> {
+    let mut builder = WorldBuilder::new();
+    builder.register_component::<Position>();
+    builder.register_component::<Velocity>();
+    builder.register_component::<Frozen>();
+    builder
+}
+
+fn spawn(mut editor: EntityEditor, position: f32, velocity: f32) -> Entity {
+    editor.add(Position(position));
+    editor.add(Velocity(velocity));
+    let entity = editor.entity;
+    editor.commit();
+    entity
+}
+
+#[test]
+fn process() {
+    let mut builder = builder();
+    builder.register_system(Movement);
+    let mut world = builder.build();
+
+    let moving = spawn(world.create(), 0.0, 2.0);
+    let frozen = spawn(world.create(), 0.0, 2.0);
+    {
+        let mut editor = world.editor(frozen);
+        editor.add(Frozen);
+        editor.commit();
+    }
+
+    world.process(Duration::from_millis(500));
+    world.process(Duration::from_millis(500));
+
+    assert_eq!(world.editor(moving).get::<Position>(), Some(&Position(2.0)));
+    assert_eq!(world.editor(frozen).get::<Position>(), Some(&Position(0.0)));
+}
+
+#[test]
+fn systems_run_in_order() {
+    let mut builder = builder();
+    builder.register_system(Movement);
+    builder.register_system(Bounds);
+    let mut world = builder.build();
+
+    let slow = spawn(world.create(), 0.0, 1.0);
+    let fast = spawn(world.create(), 0.0, 20.0);
+
+    world.process(Duration::from_secs(1));
+
+    assert!(world.is_alive(slow));
+    assert!(!world.is_alive(fast));
+}
+
+// Freezes every moving entity in one go.
+struct Freeze;
+impl System for Freeze {
+    type Aspect = (Position, Velocity, Not<Frozen>);
+
+    fn process(&mut self, context: &mut impl Context, _delta: Duration, entities: Vec<Entity>) {
+        let mut batch = context.batch();
+        for entity in entities {
+            batch.add(entity, Frozen);
+        }
+    }
+}
+
+#[test]
+fn batch_committed_on_drop() {
+    let mut builder = builder();
+    builder.set_drop_policy(DropPolicy::Commit);
+    builder.register_system(Freeze);
+    builder.register_system(Movement);
+    let mut world = builder.build();
+
+    let first = spawn(world.create(), 0.0, 1.0);
+    let second = spawn(world.create(), 0.0, 1.0);
+    world.process(Duration::from_secs(1));
+
+    assert!(world.editor(first).contains::<Frozen>());
+    assert!(world.editor(second).contains::<Frozen>());
+    assert_eq!(world.editor(first).get::<Position>(), Some(&Position(0.0)));
+}